@@ -1,6 +1,7 @@
 use std::{collections::HashSet, fmt::Display, fs};
 
 use nom::{character::complete::{self, char}, multi::many0, sequence::{pair, preceded, separated_pair}, IResult, Parser};
+use utils::IntervalSet;
 
 #[allow(dead_code)]
 fn example() -> String {
@@ -32,10 +33,10 @@ fn test_p1() {
     assert_eq!(part1(&example()), 1227775554);
 }
 
-/*#[test]
+#[test]
 fn testp1_a() {
     assert_eq!(IdRange::new(12, 22).sum_invalid(), 22)
-}*/
+}
 
 #[test]
 fn test_p2() {
@@ -96,14 +97,22 @@ impl Invalidator {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 struct IdRange {
-    min: usize,
-    max: usize
+    ids: IntervalSet<usize>
 }
 impl IdRange {
     fn new(min: usize, max: usize) -> Self {
-        Self { min, max }
+        Self { ids: IntervalSet::from_iter([min..=max]) }
+    }
+
+    // an empty range has nothing below 0, so every sum stops straight away
+    fn min(&self) -> usize {
+        self.ids.min().unwrap_or(0)
+    }
+
+    fn max(&self) -> usize {
+        self.ids.max().unwrap_or(0)
     }
 
     fn parse(input: &str) -> IResult<&str, Self> {
@@ -115,7 +124,9 @@ impl IdRange {
         .parse(input)
     }
 
-    fn parse_list(input: &str) -> IResult<&str, Vec<Self>> {
+    /// Every range keeps its own set rather than being merged into one: ids are summed per listed
+    /// range, so an id covered by two overlapping ranges counts twice, which a merged set would lose
+    fn parse_ranges(input: &str) -> IResult<&str, Vec<Self>> {
         pair(
             Self::parse,
             many0(preceded(char(','), Self::parse))
@@ -126,12 +137,12 @@ impl IdRange {
     /* PART 1 */
 
     fn real_max(&self, base: usize) -> usize {
-        self.max.min(base * (base - 2))
+        self.max().min(base * (base - 2))
     }
 
     fn real_min(&self, exp: u32) -> usize {
         let base = 1 + 10usize.pow(exp);
-        self.min.max(base * 10usize.pow(exp - 1))
+        self.min().max(base * 10usize.pow(exp - 1))
     }
 
     fn count_multiples(&self, exp: u32, base: usize) -> usize {
@@ -163,7 +174,7 @@ impl IdRange {
         let mut exp = 1;
         loop {
             let multiplier = 1 + 10usize.pow(exp);
-            if multiplier > self.max {
+            if multiplier > self.max() {
                 break;
             }
             sum += self.sum_multiples(exp);
@@ -175,21 +186,23 @@ impl IdRange {
     /* PART 2 */
 
     fn collect_part2(&self, inv: Invalidator, invalid: &mut HashSet<usize>) {
-        let min = self.min.max(inv.min());
-        let max = self.max.min(inv.max());
         let divisor = inv.divisor();
+        let legal = IntervalSet::from_iter([inv.min()..=inv.max()]);
 
-        if cfg!(test) {
-            print!("Multiples[[{} {}] {}]({} -> {}) for {}:", inv.part_len, inv.repeats, divisor, min, max, self)
-        }
+        for ids in &self.ids.intersection(&legal) {
+            let (min, max) = (*ids.start(), *ids.end());
+            if cfg!(test) {
+                print!("Multiples[[{} {}] {}]({} -> {}) for {}:", inv.part_len, inv.repeats, divisor, min, max, self)
+            }
 
-        let mut highest = max - max % divisor;
-        while highest >= min {
-            if cfg!(test) { print!(" {}", highest); }
-            invalid.insert(highest);
-            highest -= divisor;
+            let mut highest = max - max % divisor;
+            while highest >= min {
+                if cfg!(test) { print!(" {}", highest); }
+                invalid.insert(highest);
+                highest -= divisor;
+            }
+            if cfg!(test) { println!(); }
         }
-        if cfg!(test) { println!(); }
     }
 
     fn sum_part2(&self) -> usize {
@@ -200,7 +213,7 @@ impl IdRange {
             'Inner: for repeats in 2.. {
                 let inv = Invalidator::new(exp, repeats);
 
-                if inv.min() > self.max {
+                if inv.min() > self.max() {
                     if repeats == 2 {
                         break 'Outer;
                     } else {
@@ -208,7 +221,7 @@ impl IdRange {
                     }
                 }
 
-                if inv.max() < self.min {
+                if inv.max() < self.min() {
                     continue 'Inner;
                 }
 
@@ -221,13 +234,13 @@ impl IdRange {
 }
 impl Display for IdRange {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}-{}", self.min, self.max)
+        write!(f, "{}-{}", self.min(), self.max())
     }
 }
 
 fn part1(data: &str) -> usize {
     let ranges = {
-        let (remainder, ranges) = IdRange::parse_list(data).unwrap();
+        let (remainder, ranges) = IdRange::parse_ranges(data).unwrap();
         assert_eq!(remainder.len(), 0, "Non-empty remainder: '{}'", remainder);
         ranges
     };
@@ -237,11 +250,10 @@ fn part1(data: &str) -> usize {
 
 fn part2(data: &str) -> usize {
     let ranges = {
-        let (remainder, ranges) = IdRange::parse_list(data).unwrap();
+        let (remainder, ranges) = IdRange::parse_ranges(data).unwrap();
         assert_eq!(remainder.len(), 0, "Non-empty remainder: '{}'", remainder);
         ranges
     };
 
     ranges.iter().map(IdRange::sum_part2).sum()
 }
-
@@ -1,6 +1,7 @@
-use std::fs;
+use std::{collections::VecDeque, env, fs, path::PathBuf, time::Duration};
 
 use char_enum_impl::char_enum;
+use utils::{heatmap, legend, print_grid, Animator, BitGrid, Color, ColorScale, Connectivity, GifEncoder, GridMap, HtmlReport, Point, Style, StyledChar};
 
 #[allow(dead_code)]
 fn example() -> String {
@@ -31,6 +32,40 @@ fn main() {
 
     if PART2 {
         println!("Part 2: {}", part2(contents));
+
+        if env::args().any(|arg| arg == "--steps") {
+            let mut field = Field::parse(contents);
+            field.calculate_adjacencies();
+            for (wave, removed) in field.remove_all().iter().enumerate() {
+                println!("Wave {}: removed {} rolls", wave + 1, removed);
+            }
+        }
+
+        if env::args().any(|arg| arg == "--heatmap") {
+            let mut field = Field::parse(contents);
+            field.calculate_adjacencies();
+            field.print_heatmap();
+        }
+
+        if let Some(mut animator) = Animator::from_args() {
+            Field::wave_frames(contents, |grid, caption| animator.frame(grid, caption));
+            animator.finish();
+        }
+
+        if let Some(path) = env::args().find_map(|arg| arg.strip_prefix("--gif=").map(PathBuf::from)) {
+            let mut gif = GifEncoder::new();
+            let color = |c: &StyledChar| c.style.fg.unwrap_or(Color::BLACK);
+            Field::wave_frames(contents, |grid, _| gif.add_grid(grid, 4, color, Duration::from_millis(150)));
+            gif.save(&path).expect("Failed to save gif");
+            println!("Saved {} frames to {}", gif.len(), path.display());
+        }
+
+        if let Some(path) = env::args().find_map(|arg| arg.strip_prefix("--html=").map(PathBuf::from)) {
+            let mut report = HtmlReport::new("Day 04: paper roll removal");
+            Field::wave_frames(contents, |grid, caption| { report.frame(grid, caption); });
+            report.save(&path).expect("Failed to save report");
+            println!("Saved {} frames to {}", report.len(), path.display());
+        }
     }
 }
 
@@ -46,6 +81,99 @@ fn test_p2() {
     }
 }
 
+/// Straightforward reference: count neighbours by hand, remove every accessible roll at once
+#[cfg(test)]
+fn brute_force(data: &str) -> (usize, usize) {
+    let mut paper: Vec<Vec<bool>> = data.lines().map(|l| l.chars().map(|c| c == '@').collect()).collect();
+    let (height, width) = (paper.len() as isize, paper[0].len() as isize);
+
+    let accessible = |paper: &Vec<Vec<bool>>| {
+        let mut out = vec![];
+        for y in 0..height {
+            for x in 0..width {
+                if !paper[y as usize][x as usize] {
+                    continue;
+                }
+                let mut count = 0;
+                for (ny, nx) in [(y - 1, x - 1), (y - 1, x), (y - 1, x + 1), (y, x - 1), (y, x + 1), (y + 1, x - 1), (y + 1, x), (y + 1, x + 1)] {
+                    if 0 <= ny && ny < height && 0 <= nx && nx < width && paper[ny as usize][nx as usize] {
+                        count += 1;
+                    }
+                }
+                if count < 4 {
+                    out.push((y as usize, x as usize));
+                }
+            }
+        }
+        out
+    };
+
+    let first = accessible(&paper).len();
+    let mut total = 0;
+    loop {
+        let removed = accessible(&paper);
+        if removed.is_empty() {
+            break;
+        }
+        total += removed.len();
+        removed.into_iter().for_each(|(y, x)| paper[y][x] = false);
+    }
+
+    (first, total)
+}
+
+#[cfg(test)]
+fn generate(width: usize, height: usize, seed: u64) -> String {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..height)
+        .map(|_| (0..width).map(|_| if rng.random_bool(0.7) { '@' } else { '.' }).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn test_rectangular() {
+    // a solid 5x2 block peels from the corners inwards, either way up
+    assert_eq!((part1("@@@@@\n@@@@@"), part2("@@@@@\n@@@@@")), (4, 10));
+    assert_eq!((part1("@@\n@@\n@@\n@@\n@@"), part2("@@\n@@\n@@\n@@\n@@")), (4, 10));
+
+    for (seed, (width, height)) in [(40, 7), (7, 40), (1, 25), (25, 1), (33, 12), (12, 33)].into_iter().enumerate() {
+        let data = generate(width, height, seed as u64);
+        assert_eq!((part1(&data), part2(&data)), brute_force(&data), "{}x{}:\n{}", width, height, data);
+    }
+}
+
+#[test]
+fn test_waves() {
+    use utils::{compare_grids, to_plain_string, Automaton, UpdateMode};
+
+    let rule = |tile, count| if count < 4 { Tile::Floor } else { tile };
+    let render = |tiles: &Vec<Vec<Tile>>| tiles.grid_map(|tile| StyledChar::of(tile.encode()));
+
+    for data in [example(), generate(40, 30, 1), generate(17, 50, 2)] {
+        let mut field = Field::parse(&data);
+        field.calculate_adjacencies();
+        let mut states = vec![];
+        let waves = field.remove_all_with(|field, _| states.push(field.tiles.clone()));
+        assert_eq!(states.len(), waves.len());
+
+        // the generic automaton is only a reference here: part 2 keeps its own FIFO, which is linear
+        // where the engine's ordered worklist is not. Both must leave the same grid after every wave.
+        let tiles = Field::parse(&data).tiles;
+        let mut automaton = Automaton::new(tiles, Connectivity::Eight, UpdateMode::Asynchronous, |&tile| tile == Tile::Paper);
+
+        for (wave, (state, removed)) in states.iter().zip(&waves).enumerate() {
+            assert_eq!(automaton.step(&rule), *removed, "wave {}", wave + 1);
+
+            let (sides, diff) = compare_grids(&render(state), &render(automaton.cells()), 2);
+            assert!(diff.is_empty(), "wave {}: {}\n{}", wave + 1, diff, to_plain_string(&sides));
+        }
+        assert_eq!(automaton.step(&rule), 0);
+    }
+}
+
 #[char_enum]
 enum Tile {
     Paper = '@',
@@ -75,124 +203,132 @@ impl Field {
         for y in 0..self.height {
             for x in 0..self.width {
                 if let Tile::Floor = self.tiles[y][x] {
+                    self.adjacencies[y][x] = usize::MAX;
                     continue;
                 }
 
-                let y = y as isize;
-                let x = x as isize;
-
-                for yo in -1..=1 {
-                    for xo in -1..=1 {
-                        if yo == 0 && xo == 0 {
-                            continue;
-                        }
-
-                        let y = y + yo;
-                        let x = x + xo;
-
-                        if y < 0 || x < 0 {
-                            continue;
-                        }
-
-                        let y = y as usize;
-                        let x = x as usize;
-
-                        if y >= self.height || x >= self.width {
-                            continue;
-                        }
-
-                        self.adjacencies[y][x] += 1;
-                    }
-                }
+                self.adjacencies[y][x] = Connectivity::Eight.neighbours(Point { x, y }, self.width, self.height)
+                    .filter(|n| self.tiles[n.y][n.x] == Tile::Paper)
+                    .count();
             }
         }
+    }
 
-        for y in 0..self.height {
-            for x in 0..self.height {
-                if let Tile::Floor = self.tiles[y][x] {
-                    self.adjacencies[y][x] = usize::MAX;
-                }
-            }
+    /// Rolls in white, with the ones removed in the last wave marked in red
+    fn render(&self, removed: &[Point<usize>]) -> Vec<Vec<StyledChar>> {
+        let mut grid = self.tiles.grid_map(|&tile| match tile {
+            Tile::Paper => StyledChar { chr: '@', style: Style::fg(Some(Color::WHITE)) },
+            Tile::Floor => StyledChar { chr: '.', style: Style::fg(Some(Color::rgb(90, 90, 90))) },
+        });
+        for pt in removed {
+            grid[pt.y][pt.x] = StyledChar { chr: 'x', style: Style::fg(Some(Color::rgb(230, 60, 60))) };
         }
+        grid
     }
 
-    fn count_accessible(&self) -> usize {
-        self.adjacencies.iter().map(|line| line.iter().filter(|v| **v < 4).count()).sum()
+    /// Parse `input` and peel it, passing the starting field and then every wave to `frame`
+    fn wave_frames<F>(input: &str, mut frame: F) where F: FnMut(&[Vec<StyledChar>], &str) {
+        let mut field = Field::parse(input);
+        field.calculate_adjacencies();
+        frame(&field.render(&[]), "start");
+        field.remove_all_with(|field, removed| frame(&field.render(removed), &format!("removed {} rolls", removed.len())));
     }
 
-    fn remove_step(&mut self) -> usize {
-        let mut count = 0;
-
-        for y in 0..self.height {
-            for x in 0..self.width {
-                if self.adjacencies[y][x] >= 4 || self.tiles[y][x] == Tile::Floor {
-                    continue;
-                }
+    /// Neighbour count of every roll, coloured from 0 to 8
+    fn print_heatmap(&self) {
+        let scale = ColorScale::viridis(0.0, 8.0);
+        let mut grid = heatmap(&self.adjacencies, &scale, |&adj| match adj {
+            usize::MAX => ('.', None),
+            adj => (char::from_digit(adj as u32, 10).unwrap(), Some(adj as f64)),
+        });
+        grid.push(vec![]);
+        grid.push(legend(&scale, 9));
+        print_grid(&grid);
+    }
 
-                for yo in -1..=1 {
-                    for xo in -1..=1 {
-                        if yo == 0 && xo == 0 {
-                            continue;
-                        }
+    fn paper(&self) -> BitGrid {
+        BitGrid::from_grid(&self.tiles, |&tile| tile == Tile::Paper)
+    }
 
-                        let y = (y as isize) + yo;
-                        let x = (x as isize) + xo;
+    /// Paper with fewer than four neighbouring paper tiles
+    fn accessible(&self) -> BitGrid {
+        let paper = self.paper();
+        &paper & &paper.neighbour_counts(Connectivity::Eight).below(4)
+    }
 
-                        if y < 0 || x < 0 {
-                            continue;
-                        }
+    /// Remove rolls in waves until none is accessible, see [`Field::remove_all_with`]
+    fn remove_all(&mut self) -> Vec<usize> {
+        self.remove_all_with(|_, _| {})
+    }
 
-                        let y = y as usize;
-                        let x = x as usize;
+    /// Remove rolls until none is accessible, calling `on_wave` with the rolls removed after every
+    /// wave; returns how many were removed in each wave.
+    ///
+    /// A wave matches one in-place scan in reading order: a removal only counts towards the
+    /// rolls after it, while neighbours already passed see it in the next wave. Those late
+    /// decrements are deferred to the end of the wave, so the queue order doesn't matter. Only
+    /// rolls whose adjacency just dropped below 4 are queued.
+    fn remove_all_with<F>(&mut self, mut on_wave: F) -> Vec<usize> where F: FnMut(&Self, &[Point<usize>]) {
+        let mut waves = vec![];
+        let mut current: VecDeque<Point<usize>> = (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| Point { x, y }))
+            .filter(|&pt| self.tiles[pt.y][pt.x] == Tile::Paper && self.adjacencies[pt.y][pt.x] < 4)
+            .collect();
+        let mut next = VecDeque::new();
+        let mut deferred = vec![];
+
+        while !current.is_empty() {
+            let mut removed = vec![];
+
+            while let Some(pt) = current.pop_front() {
+                removed.push(pt);
+                self.tiles[pt.y][pt.x] = Tile::Floor;
+                self.adjacencies[pt.y][pt.x] = usize::MAX;
+
+                for n in Connectivity::Eight.neighbours(pt, self.width, self.height) {
+                    if self.tiles[n.y][n.x] == Tile::Floor {
+                        continue;
+                    }
 
-                        if y >= self.height || x >= self.height {
-                            continue;
-                        }
+                    if (n.y, n.x) < (pt.y, pt.x) {
+                        deferred.push(n);
+                        continue;
+                    }
 
-                        let adj = self.adjacencies[y][x];
-                        if 0 < adj && adj < usize::MAX {
-                            self.adjacencies[y][x] = adj - 1;
-                        }
+                    self.adjacencies[n.y][n.x] -= 1;
+                    if self.adjacencies[n.y][n.x] == 3 {
+                        current.push_back(n);
                     }
                 }
-
-                count += 1;
-                self.tiles[y][x] = Tile::Floor;
             }
-        }
-
-        count
-    }
 
-    fn remove_all(&mut self) -> usize {
-        let mut count = 0;
-
-        loop {
-            let increment = self.remove_step();
-            if increment == 0 {
-                break;
+            for n in deferred.drain(..) {
+                if self.tiles[n.y][n.x] == Tile::Paper {
+                    self.adjacencies[n.y][n.x] -= 1;
+                    if self.adjacencies[n.y][n.x] == 3 {
+                        next.push_back(n);
+                    }
+                }
             }
-            count += increment;
+
+            waves.push(removed.len());
+            on_wave(self, &removed);
+            std::mem::swap(&mut current, &mut next);
         }
 
-        count
+        waves
     }
 }
 
 fn part1(data: &str) -> usize {
-    let field = {
-        let mut field = Field::parse(data);
-        field.calculate_adjacencies();
-        field
-    };
-
-    field.count_accessible()
+    let field = Field::parse(data);
+    field.accessible().count_ones()
 }
 
 fn part2(data: &str) -> usize {
     let mut field = Field::parse(data);
     field.calculate_adjacencies();
 
-    field.remove_all()
+    field.remove_all().into_iter().sum()
 }
 
@@ -1,7 +1,7 @@
-use std::fs;
+use std::{fs, ops::RangeInclusive};
 
 use nom::{character::complete, multi::separated_list1, sequence::separated_pair, IResult, Parser};
-use utils::parse_complete;
+use utils::{parse_complete, IntervalSet};
 
 #[allow(dead_code)]
 fn example() -> String {
@@ -48,100 +48,41 @@ fn test_p2() {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
-struct FreshRange {
-    min: usize,
-    max: usize
-}
-impl FreshRange {
-    fn parse(input: &str) -> IResult<&str, Self> {
-        separated_pair(complete::usize, complete::char('-'), complete::usize)
-            .map(|(min, max)| Self { min, max })
-            .parse(input)
-    }
-
-    fn min(&self) -> usize {
-        self.min
-    }
-
-    #[allow(dead_code)]
-    fn max(&self) -> usize {
-        self.max
-    }
-
-    fn contains(&self, other: usize) -> bool {
-        self.min <= other && other <= self.max
-    }
-
-    fn merge(&self, other: &FreshRange) -> Option<FreshRange> {
-        if self.min > other.max || other.min > self.max {
-            None
-        } else {
-            Some(FreshRange { min: self.min.min(other.min), max: self.max.max(other.max) })
-        }
-    }
-
-    fn len(&self) -> usize {
-        self.max - self.min + 1
-    }
-}
-
 #[derive(Clone, Debug)]
 struct Kitchen {
-    fresh: Vec<FreshRange>,
+    fresh: IntervalSet<usize>,
     available: Vec<usize>
 }
 impl Kitchen {
+    fn parse_range(input: &str) -> IResult<&str, RangeInclusive<usize>> {
+        separated_pair(complete::usize, complete::char('-'), complete::usize)
+            .map(|(min, max)| min..=max)
+            .parse(input)
+    }
+
     fn parse(input: &str) -> IResult<&str, Self> {
-        let fresh_parser = separated_list1(complete::line_ending, FreshRange::parse);
+        let fresh_parser = separated_list1(complete::line_ending, Self::parse_range);
         let available_parser = separated_list1(complete::line_ending, complete::usize);
         let full_parser = separated_pair(fresh_parser, nom::bytes::tag("\n\n"), available_parser);
 
-        full_parser.map(|(fresh, available)| Self { fresh, available }).parse(input)
-    }
-
-    fn unify_fresh(&mut self) {
-        self.fresh.sort_by_key(FreshRange::min);
-        self.fresh = self.fresh.iter().fold(vec![], |mut v, fr| {
-            let prev = v.last();
-            match prev {
-                Some(prev) => {
-                    match prev.merge(fr) {
-                        Some(merged) => {
-                            v.pop();
-                            v.push(merged);
-                        }
-                        None => v.push(*fr)
-                    }
-                }
-                None => v.push(*fr)
-            }
-            v
-        });
-    }
-
-    fn contains(&self, ingredient: usize) -> bool {
-        self.fresh.iter().any(|fr| fr.contains(ingredient))
+        full_parser.map(|(fresh, available)| Self { fresh: fresh.into_iter().collect(), available }).parse(input)
     }
 
     fn count_fresh_and_available(&self) -> usize {
-        self.available.iter().filter(|v| self.contains(**v)).count()
+        self.available.iter().filter(|v| self.fresh.contains(**v)).count()
     }
 
     fn count_fresh(&self) -> usize {
-        self.fresh.iter().map(FreshRange::len).sum()
+        self.fresh.len()
     }
 }
 
 fn part1(data: &str) -> usize {
-    let mut kitchen = parse_complete(&mut Kitchen::parse, data);
-    kitchen.unify_fresh();
+    let kitchen = parse_complete(&mut Kitchen::parse, data);
     kitchen.count_fresh_and_available()
 }
 
 fn part2(data: &str) -> usize {
-    let mut kitchen = parse_complete(&mut Kitchen::parse, data);
-    kitchen.unify_fresh();
+    let kitchen = parse_complete(&mut Kitchen::parse, data);
     kitchen.count_fresh()
 }
-
@@ -1,8 +1,8 @@
-use std::fs;
+use std::{env, fs, path::PathBuf, time::Duration};
 
-use bitvec::vec::BitVec;
+use bitvec::{slice::BitSlice, vec::BitVec};
 use char_enum_impl::char_enum;
-use utils::GridMap;
+use utils::{heatmap, legend, print_grid, shifted_row, Animator, BitGrid, Color, ColorScale, GifEncoder, Mapping, Point, Style, StyledChar};
 
 #[allow(dead_code)]
 fn example() -> String {
@@ -40,6 +40,26 @@ fn main() {
     if PART2 {
         println!("Part 2: {}", part2(contents));
     }
+
+    if env::args().any(|arg| arg == "--heatmap") {
+        Manifold::parse(contents).print_heatmap();
+    }
+
+    if let Some(mut animator) = Animator::from_args() {
+        Manifold::parse(contents).beam_frames(|grid, caption| animator.frame(grid, caption));
+        animator.finish();
+    }
+
+    if let Some(path) = env::args().find_map(|arg| arg.strip_prefix("--gif=").map(PathBuf::from)) {
+        let mut gif = GifEncoder::new();
+        let color = |c: &StyledChar| match c.chr {
+            '.' => Color::rgb(30, 30, 40),
+            _ => c.style.fg.unwrap_or(Color::rgb(200, 200, 200)),
+        };
+        Manifold::parse(contents).beam_frames(|grid, _| gif.add_grid(grid, 4, color, Duration::from_millis(80)));
+        gif.save(&path).expect("Failed to save gif");
+        println!("Saved {} frames to {}", gif.len(), path.display());
+    }
 }
 
 #[test]
@@ -63,45 +83,86 @@ enum Tile {
 
 #[derive(Debug)]
 struct Manifold {
-    grid: Vec<Vec<Tile>>,
-    splitters: Vec<BitVec>
+    start: BitVec,
+    splitters: BitGrid
 }
 impl Manifold {
     fn parse(input: &str) -> Self {
         let grid = utils::parse_grid::<Tile>(input);
-        let splitters = grid.row_map(|&tile| tile == Tile::Splitter);
+        let splitters = BitGrid::from_grid(&grid, |&tile| tile == Tile::Splitter);
         assert!(grid[0].contains(&Tile::Start), "first row must contain start");
+        let start = grid[0].iter().map(|&tile| tile == Tile::Start).collect();
 
-        Self { grid, splitters }
+        Self { start, splitters }
+    }
+
+    /// Beams hitting a splitter in this row, and the beams leaving the row
+    fn step(beams: &BitVec, splitters: &BitSlice) -> (BitVec, BitVec) {
+        let split = beams.clone() & splitters;
+        let mut out = beams.clone() ^ &split; // remove all split beams from the beam
+        out |= &shifted_row(&split, -1);
+        out |= &shifted_row(&split, 1);
+        (split, out)
     }
 
     fn count_splits(&self) -> usize {
         let mut count = 0;
-        let mut beams = self.grid[0].iter().map(|&tile| tile == Tile::Start).collect::<BitVec>();
+        let mut beams = self.start.clone();
 
-        for splitters in self.splitters.iter().skip(1) {
-            let split = beams.clone() & splitters;
-            beams ^= &split; // remove all split beams from the beam
+        for splitters in self.splitters.rows().skip(1) {
+            let (split, next) = Self::step(&beams, splitters);
             count += split.count_ones();
-
-            beams |= &split[1..];
-            beams[1..] |= split;
+            beams = next;
         }
 
         count
     }
 
+    /// Show the beams moving down one row per frame, with hit splitters in red
+    fn beam_frames<F>(&self, mut frame: F) where F: FnMut(&[Vec<StyledChar>], &str) {
+        let mut grid: Vec<Vec<StyledChar>> = (0..self.splitters.height()).map(|y| (0..self.splitters.width()).map(|x| {
+            if y == 0 && self.start[x] {
+                StyledChar::of('S')
+            } else if self.splitters.get(Point { x, y }) {
+                StyledChar::of('^')
+            } else {
+                StyledChar::of('.')
+            }
+        }).collect()).collect();
+
+        let beam = StyledChar { chr: '|', style: Style::fg(Some(Color::rgb(250, 220, 60))) };
+        let hit = Style::fg(Some(Color::rgb(230, 60, 60)));
+
+        let mut beams = self.start.clone();
+        let mut count = 0;
+        frame(&grid, "start");
+
+        for (y, splitters) in self.splitters.rows().enumerate().skip(1) {
+            let (split, next) = Self::step(&beams, splitters);
+            count += split.count_ones();
+
+            split.iter_ones().for_each(|x| grid[y][x].style = hit);
+            next.iter_ones().filter(|&x| !splitters[x]).for_each(|x| grid[y][x] = beam);
+            beams = next;
+
+            frame(&grid, &format!("row {}, {} splits", y, count));
+        }
+    }
+
     fn count_timelines(&self) -> usize {
-        let mut beams = self.grid[0].iter().map(|&tile| tile == Tile::Start).collect::<BitVec>();
+        self.timelines().pop().unwrap().into_iter().sum()
+    }
+
+    /// Number of timelines with a beam in each cell, row by row
+    fn timelines(&self) -> Vec<Vec<usize>> {
+        let mut beams = self.start.clone();
         let mut count = beams.iter().map(|v| if *v { 1 } else { 0 }).collect::<Vec<usize>>();
         let cols = count.len();
+        let mut rows = vec![count.clone()];
 
-        for splitters in self.splitters.iter().skip(1) {
-            let split = beams.clone() & splitters;
-            beams ^= &split; // remove all split beams from the beam
-
-            beams |= &split[1..];
-            beams[1..] |= &split;
+        for splitters in self.splitters.rows().skip(1) {
+            let (split, next) = Self::step(&beams, splitters);
+            beams = next;
 
             let mut new_count = count.iter().zip(split.iter()).map(|(&count, split)| if *split { 0 } else { count }).collect::<Vec<usize>>();
             split.iter_ones().for_each(|idx| {
@@ -114,9 +175,29 @@ impl Manifold {
                 }
             });
             count = new_count;
+            rows.push(count.clone());
         }
 
-        count.into_iter().sum()
+        rows
+    }
+
+    /// Timelines per cell on a log scale, splitters shown as ^
+    fn print_heatmap(&self) {
+        let rows = self.timelines();
+        let max = rows.iter().flatten().copied().max().unwrap_or(0);
+        let scale = ColorScale::viridis(0.0, max as f64).with_mapping(Mapping::Log);
+
+        let cells: Vec<Vec<(bool, usize)>> = rows.iter().enumerate()
+            .map(|(y, row)| row.iter().enumerate().map(|(x, &n)| (self.splitters.get(Point { x, y }), n)).collect())
+            .collect();
+        let mut grid = heatmap(&cells, &scale, |&(splitter, n)| match (splitter, n) {
+            (true, _) => ('^', None),
+            (false, 0) => ('.', None),
+            (false, n) => ('|', Some(n as f64)),
+        });
+        grid.push(vec![]);
+        grid.push(legend(&scale, 16));
+        print_grid(&grid);
     }
 }
 
@@ -1,7 +1,7 @@
-use std::{collections::HashSet, fmt::Display, fs};
+use std::{collections::HashSet, env, fs, io, path::{Path, PathBuf}};
 
-use nom::{character::complete, multi::separated_list1, sequence::separated_pair, IResult, Parser};
-use utils::parse_complete;
+use nom::{character::complete, multi::separated_list1, IResult, Parser};
+use utils::{parse_complete, Color, Paint, Point, Svg};
 
 #[allow(dead_code)]
 fn example() -> String {
@@ -43,6 +43,15 @@ fn main() {
     if PART2 {
         println!("Part 2: {}", part2(contents));
     }
+
+    if let Some(path) = env::args().find_map(|arg| arg.strip_prefix("--svg=").map(PathBuf::from)) {
+        let mut cave = parse_complete(&mut Cave::parse, contents);
+        cave.populate_distances();
+        let n = cave.candidates.len().min(1000);
+        cave.connect_n(n);
+        cave.save_svg(n, &path).expect("Failed to save svg");
+        println!("Saved nets to {}", path.display());
+    }
 }
 
 #[test]
@@ -57,32 +66,7 @@ fn test_p2() {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
-struct JunctionBox {
-    x: usize,
-    y: usize,
-    z: usize
-}
-impl JunctionBox {
-    fn parse(input: &str) -> IResult<&str, Self> {
-        separated_pair(complete::usize, complete::char(','), separated_pair(complete::usize, complete::char(','), complete::usize))
-            .map(|(x, (y, z))| Self { x, y, z})
-            .parse(input)
-    }
-
-    fn dist_sq(&self, other: &Self) -> usize {
-        let dx = self.x as isize - other.x as isize;
-        let dy = self.y as isize - other.y as isize;
-        let dz = self.z as isize - other.z as isize;
-
-        (dx*dx + dy*dy + dz*dz) as usize
-    }
-}
-impl Display for JunctionBox {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{},{},{}", self.x, self.y, self.z)
-    }
-}
+type JunctionBox = utils::Point3<usize>;
 
 #[derive(Clone, Copy, Debug)]
 struct ConnectionCandidate {
@@ -135,7 +119,7 @@ impl Cave {
     fn populate_distances(&mut self) {
         for i in 0..self.boxes.len() {
             for j in (i+1)..self.boxes.len() {
-                let dist_sq = self.boxes[i].0.dist_sq(&self.boxes[j].0);
+                let dist_sq = self.boxes[i].0.dist_sq(self.boxes[j].0);
                 self.candidates.push(ConnectionCandidate { dist_sq, id_a: i, id_b: j });
             }
         }
@@ -242,6 +226,25 @@ impl Cave {
         sizes.into_iter().rev().take(3).product()
     }
 
+    /// XY projection of the boxes and the first `n` connections, coloured by net
+    fn save_svg(&self, n: usize, path: &Path) -> io::Result<()> {
+        let xy = |id: usize| Point { x: self.boxes[id].0.x as f64, y: self.boxes[id].0.y as f64 };
+        let color = |id: usize| self.boxes[id].1.map_or(Color::rgb(128, 128, 128), Color::random_from_seed);
+
+        let mut svg = Svg::new();
+        for cc in &self.candidates[..n] {
+            svg.line(xy(cc.id_a), xy(cc.id_b), Paint::stroke(color(cc.id_a), 1.0).with_opacity(0.8));
+        }
+
+        let max_coord = self.boxes.iter().map(|(jb, _)| jb.x.max(jb.y)).max().unwrap_or(0);
+        let radius = (max_coord as f64 / 250.0).max(1.0);
+        for id in 0..self.boxes.len() {
+            svg.circle(xy(id), radius, Paint::fill(color(id)));
+        }
+
+        svg.save(path)
+    }
+
     fn connect_all(&mut self) -> usize {
         let mut last_product = 0;
 
@@ -1,8 +1,8 @@
-use std::{fs::{self, File}, io::Write, path::Path};
+use std::{env, fs, io, path::{Path, PathBuf}};
 
 use char_enum_impl::{char_enum, data_enum};
-use nom::{character::complete, multi::separated_list1, sequence::separated_pair, IResult, Parser};
-use utils::{make_grid, parse_complete};
+use nom::{character::complete, multi::separated_list1, IResult, Parser};
+use utils::{fill_exterior, make_grid, parse_complete, Color, CompressedGrid, Connectivity, HtmlReport, Image, OrthogonalPolygon, Paint, Style, StyledChar, Svg};
 
 #[allow(dead_code)]
 fn example() -> String {
@@ -32,6 +32,24 @@ fn main() {
     if PART2 {
         println!("Part 2: {}", part2(contents));
     }
+
+    if let Some(path) = env::args().find_map(|arg| arg.strip_prefix("--image=").map(PathBuf::from)) {
+        let map = parse_complete(&mut Map::parse, contents);
+        map.save_image(&path, 4).expect("Failed to save image");
+        println!("Saved compacted grid to {}", path.display());
+    }
+
+    if let Some(path) = env::args().find_map(|arg| arg.strip_prefix("--svg=").map(PathBuf::from)) {
+        let map = parse_complete(&mut Map::parse, contents);
+        map.save_svg(&path).expect("Failed to save svg");
+        println!("Saved tile loop to {}", path.display());
+    }
+
+    if let Some(path) = env::args().find_map(|arg| arg.strip_prefix("--html=").map(PathBuf::from)) {
+        let map = parse_complete(&mut Map::parse, contents);
+        map.save_html(&path).expect("Failed to save report");
+        println!("Saved compacted grid to {}", path.display());
+    }
 }
 
 #[test]
@@ -46,13 +64,29 @@ fn test_p2() {
     }
 }
 
-type Point = utils::Point<usize>;
-type Compactor = utils::Compactor<usize>;
+#[test]
+fn test_polygon_area() {
+    let map = parse_complete(&mut Map::parse, &example());
+    let covered = map.build_grid(false).iter().flatten().filter(|&&t| t != Tile::Floor).count();
+    assert_eq!(map.polygon.covered_points(), covered as i128);
+    assert_eq!(map.weights().total(), covered);
+}
 
-fn area(a: Point, b: Point) -> usize {
-    (1 + a.x.max(b.x) - a.x.min(b.x)) * (1 + a.y.max(b.y) - a.y.min(b.y))
+#[test]
+fn test_rect_validation() {
+    let map = parse_complete(&mut Map::parse, &example());
+    let weights = map.weights();
+    for &a in &map.red_tiles {
+        for &b in &map.red_tiles {
+            assert_eq!(weights.is_fully_inside(Rect::from_corners(a, b)), map.polygon.contains_rect(a, b), "{} {}", a, b);
+        }
+    }
 }
 
+type Point = utils::Point<usize>;
+type Compactor = utils::Compactor<usize>;
+type Rect = utils::Rect<usize>;
+
 #[char_enum]
 #[data_enum[(u8, u8, u8)]]
 enum Tile {
@@ -67,20 +101,9 @@ impl Tile {
         print!("{}", utils::colorize(&format!("{}", self.encode()), r, g, b));
     }
 
-    fn save_grid(grid: &Vec<Vec<Self>>, fname: &Path) -> std::io::Result<()> {
-        let mut file = File::create(fname)?;
-        file.write_all("P3\n".as_bytes())?;
-        file.write_all(format!("{} {}\n", grid[0].len(), grid.len()).as_bytes())?;
-        file.write_all("255\n".as_bytes())?;
-
-        for row in grid {
-            for tile in row {
-                let (r, g, b) = tile.value();
-                file.write_all(format!("{} {} {}\n", r, g, b).as_bytes())?;
-            }
-        }
-
-        Ok(())
+    fn color(&self) -> Color {
+        let (r, g, b) = self.value();
+        Color::rgb(r, g, b)
     }
 }
 
@@ -89,14 +112,11 @@ struct Map {
     red_tiles: Vec<Point>,
     compactor: Compactor,
     compacted_tiles: Vec<Point>,
+    polygon: OrthogonalPolygon<usize>,
 }
 impl Map {
-    fn parse_point(input: &str) -> IResult<&str, Point> {
-        separated_pair(complete::usize, complete::char(','), complete::usize).map(|(a, b)| Point { x: a, y: b }).parse(input)
-    }
-
     fn parse(input: &str) -> IResult<&str, Self> {
-        separated_list1(complete::line_ending, Self::parse_point).map(|red_tiles| {
+        separated_list1(complete::line_ending, Point::parse).map(|red_tiles| {
             let x0 = red_tiles.iter().map(|p| p.x).min().unwrap();
             let y0 = red_tiles.iter().map(|p| p.y).min().unwrap();
             let p0 = Point { x: x0, y: y0 };
@@ -105,11 +125,13 @@ impl Map {
             let compactor = {
                 let mut compactor = Compactor::new();
                 red_tiles.iter().for_each(|t| compactor.add_key_point(*t));
+                compactor.freeze();
                 compactor
             };
             let compacted_tiles = red_tiles.iter().map(|t| compactor.compact(*t)).collect();
+            let polygon = OrthogonalPolygon::new(red_tiles.clone());
 
-            Self { red_tiles, compactor, compacted_tiles }
+            Self { red_tiles, compactor, compacted_tiles, polygon }
         }).parse(input)
     }
 
@@ -118,7 +140,7 @@ impl Map {
 
         for i in 0..self.red_tiles.len() {
             for j in i+1..self.red_tiles.len() {
-                max_area = max_area.max(area(self.red_tiles[i], self.red_tiles[j]));
+                max_area = max_area.max(Rect::from_corners(self.red_tiles[i], self.red_tiles[j]).area_inclusive());
             }
         }
 
@@ -155,46 +177,11 @@ impl Map {
         tiles.iter().for_each(|t| grid[*t] = Tile::Red);
 
         if compact || cfg!(test) {
-            for row in &mut grid {
-                let mut left_was_in_floor = true;
-                let mut left_ok = false;
-                for x in 0..row.len() {
-                    match row[x] {
-                        Tile::Red | Tile::Green => {
-                            if left_was_in_floor {
-                                left_ok = !left_ok;
-                                left_was_in_floor = false;
-                            }
-                        }
-                        Tile::Fill => {
-                            left_was_in_floor = true;
-                        }
-                        Tile::Floor => {
-                            left_was_in_floor = true;
-
-                            if left_ok {
-                                let mut right_was_in_floor = true;
-                                let mut right_ok = false;
-                                for &tile1 in row.iter().skip(x) {
-                                    match tile1 {
-                                        Tile::Red | Tile::Green => {
-                                            if right_was_in_floor {
-                                                right_was_in_floor = false;
-                                                right_ok = !right_ok;
-                                            }
-                                        }
-
-                                        Tile::Fill | Tile::Floor => {
-                                            right_was_in_floor = true;
-                                        }
-                                    }
-                                }
-
-                                if right_ok {
-                                    row[x] = Tile::Fill;
-                                }
-                            }
-                        }
+            let exterior = fill_exterior(&grid, Connectivity::Four, |&t| t == Tile::Floor);
+            for (row, exterior_row) in grid.iter_mut().zip(exterior) {
+                for (tile, is_exterior) in row.iter_mut().zip(exterior_row) {
+                    if *tile == Tile::Floor && !is_exterior {
+                        *tile = Tile::Fill;
                     }
                 }
             }
@@ -203,83 +190,90 @@ impl Map {
         grid
     }
 
-    fn print_part2(&self, grid: &Vec<Vec<Tile>>, ppm_path: &Path) {
-        Tile::save_grid(grid, ppm_path).unwrap();
-
-        if cfg!(test) {
-            for row in grid {
-                row.iter().for_each(|t| t.print());
-                println!();
-            }
-        }
+    /// Real area of every red or green tile, from the compacted grid
+    fn weights(&self) -> CompressedGrid<usize> {
+        CompressedGrid::from_grid(self.compactor.clone(), &self.build_grid(true), |&t| t != Tile::Floor)
     }
 
-    #[allow(clippy::needless_range_loop, clippy::ptr_arg)]
-    fn validate(a: Point, b: Point, grid: &Vec<Vec<Tile>>) -> bool {
-        let x0 = a.x.min(b.x);
-        let x1 = a.x.max(b.x);
-        let y0 = a.y.min(b.y);
-        let y1 = a.y.max(b.y);
-
-        if grid[y0][x0] == Tile::Floor || grid[y0][x1] == Tile::Floor || grid[y1][x0] == Tile::Floor || grid[y1][x1] == Tile::Floor {
-            return false;
-        }
-
-        for y in y0..=y1 {
-            let row = &grid[y];
-            if row[x0] == Tile::Floor || row[x1] == Tile::Floor {
-                return false;
-            }
-        }
-
-        for x in x0..=x1 {
-            if grid[y0][x] == Tile::Floor || grid[y1][x] == Tile::Floor {
-                return false;
-            }
+    fn print_grid(grid: &[Vec<Tile>]) {
+        for row in grid {
+            row.iter().for_each(|t| t.print());
+            println!();
         }
+    }
 
-        /*for y in y0..=y1 {
-            let row = &grid[y];
-            for x in x0..=x1 {
-                if row[x] == Tile::Floor {
-                    return false;
-                }
-            }
-        }*/
-
-        true
+    /// Save the compacted grid as an image (format from the extension), `scale` pixels per tile
+    fn save_image(&self, path: &Path, scale: usize) -> io::Result<()> {
+        Image::from_grid(&self.build_grid(true), scale, |t| t.color()).save(path)
     }
 
     fn max_area2(&self) -> usize {
-        let grid = self.build_grid(true);
+        let weights = self.weights();
         /*[bench exclude]*/ {
-            println!("Grid done");
-            self.print_part2(&grid, Path::new("compact.ppm"));
+            println!("Grid done, {} tiles filled ({} by Pick's theorem)", weights.total(), self.polygon.covered_points());
             if cfg!(test) {
+                Self::print_grid(&self.build_grid(true));
                 println!("Full");
-                self.print_part2(&self.build_grid(false), Path::new("full.ppm"));
+                Self::print_grid(&self.build_grid(false));
                 println!("Compactor: {:?}", self.compactor);
             }
         }
 
-        let mut max_area = 0;
+        self.best_rect2(&weights).map_or(0, |rect| rect.area_inclusive())
+    }
+
+    /// The largest rectangle with red corners that only covers red or green tiles
+    fn best_rect2(&self, weights: &CompressedGrid<usize>) -> Option<Rect> {
+        let mut best: Option<Rect> = None;
 
         for i in 0..self.red_tiles.len() {
             for j in i+1..self.red_tiles.len() {
-                let a = self.red_tiles[i];
-                let b = self.red_tiles[j];
-
-                let ac = self.compacted_tiles[i];
-                let bc = self.compacted_tiles[j];
-                if !Self::validate(ac, bc, &grid) {
-                    continue;
+                let rect = Rect::from_corners(self.red_tiles[i], self.red_tiles[j]);
+                if weights.is_fully_inside(rect) && best.is_none_or(|b| rect.area_inclusive() > b.area_inclusive()) {
+                    best = Some(rect);
                 }
-
-                max_area = max_area.max(area(a, b));
             }
         }
 
-        max_area
+        best
+    }
+
+    /// Save the compacted grid as a standalone HTML page
+    fn save_html(&self, path: &Path) -> io::Result<()> {
+        let grid: Vec<Vec<StyledChar>> = self.build_grid(true).iter().map(|row| row.iter().map(|t| StyledChar {
+            chr: t.encode(),
+            style: Style::fg(Some(t.color())),
+        }).collect()).collect();
+
+        let mut report = HtmlReport::new("Day 09: compacted tile loop");
+        report.frame(&grid, &format!("{} red tiles", self.red_tiles.len()));
+        report.save(path)
+    }
+
+    /// The loop of red tiles, with the part 2 rectangle highlighted
+    fn save_svg(&self, path: &Path) -> io::Result<()> {
+        let to_f64 = |pt: Point| pt.map(|v| v as f64);
+        let loop_points: Vec<_> = self.red_tiles.iter().map(|&pt| to_f64(pt)).collect();
+
+        let mut svg = Svg::new();
+        svg.polygon(&loop_points, Paint::fill(Tile::Fill.color()).with_stroke(Tile::Green.color(), 1.0));
+
+        if let Some(best) = self.best_rect2(&self.weights()) {
+            let paint = Paint::fill(Color::rgb(60, 120, 255)).with_opacity(0.5).with_stroke(Color::rgb(0, 40, 160), 2.0);
+            svg.rect(to_f64(best.min), to_f64(best.max), paint);
+        }
+
+        let radius = (self.polygon_extent() / 400.0).max(0.2);
+        for &pt in &loop_points {
+            svg.circle(pt, radius, Paint::fill(Tile::Red.color()));
+        }
+
+        svg.save(path)
+    }
+
+    fn polygon_extent(&self) -> f64 {
+        let bounds = Rect::bounding(self.red_tiles.iter().copied()).unwrap();
+        bounds.width().max(bounds.height()) as f64
     }
 }
 
@@ -0,0 +1,123 @@
+use std::{
+    env,
+    io::{self, BufRead, IsTerminal, Write},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{print_grid, StyledChar};
+
+const HIDE_CURSOR: &str = "\x1b[?25l";
+const SHOW_CURSOR: &str = "\x1b[?25h";
+/// from the cursor to the end of the screen, so a smaller frame leaves nothing of the last one
+const CLEAR_BELOW: &str = "\x1b[J";
+
+/// Shows a sequence of grids as an animation.
+///
+/// On a terminal every frame is drawn over the previous one; otherwise frames are printed one
+/// after another with no delay, so output can be piped to a file.
+#[derive(Debug)]
+pub struct Animator {
+    frame_time: Duration,
+    step: bool,
+    tty: bool,
+    frame: usize,
+    /// lines printed for the previous frame, to move back over
+    drawn: usize,
+    last: Option<Instant>,
+}
+impl Animator {
+    pub fn new(fps: f32) -> Self {
+        assert!(fps > 0.0, "frame rate must be positive");
+        Self {
+            frame_time: Duration::from_secs_f32(1.0 / fps),
+            step: false,
+            tty: io::stdout().is_terminal(),
+            frame: 0,
+            drawn: 0,
+            last: None,
+        }
+    }
+
+    /// `Some` if the program was run with `--animate`. `--fps=N` sets the frame rate (default 10)
+    /// and `--step` waits for Enter after every frame. Input is line buffered, so other keys
+    /// only echo until Enter is pressed.
+    pub fn from_args() -> Option<Self> {
+        let args: Vec<String> = env::args().collect();
+        if !args.iter().any(|a| a == "--animate") {
+            return None;
+        }
+
+        let fps = args.iter()
+            .find_map(|a| a.strip_prefix("--fps="))
+            .map(|fps| fps.parse().expect("--fps must be a number"))
+            .unwrap_or(10.0);
+        Some(Self::new(fps).stepping(args.iter().any(|a| a == "--step")))
+    }
+
+    /// Wait for Enter (not just any key) after every frame instead of using the frame rate
+    /// (only on a terminal)
+    pub fn stepping(mut self, step: bool) -> Self {
+        self.step = step;
+        self
+    }
+
+    pub fn is_tty(&self) -> bool {
+        self.tty
+    }
+
+    /// Number of frames shown so far
+    pub fn frames(&self) -> usize {
+        self.frame
+    }
+
+    pub fn frame(&mut self, grid: &[Vec<StyledChar>], caption: &str) {
+        self.frame += 1;
+
+        if !self.tty {
+            println!("Frame {}: {}", self.frame, caption);
+            print_grid(grid);
+            println!();
+            return;
+        }
+
+        if let Some(last) = self.last {
+            if !self.step {
+                thread::sleep(self.frame_time.saturating_sub(last.elapsed()));
+            }
+        }
+
+        if self.drawn == 0 {
+            print!("{}", HIDE_CURSOR);
+        } else {
+            print!("\x1b[{}A\r{}", self.drawn, CLEAR_BELOW);
+        }
+
+        let hint = if self.step { " [Enter: next frame]" } else { "" };
+        println!("Frame {}: {}{}", self.frame, caption, hint);
+        print_grid(grid);
+        self.drawn = grid.len() + 1;
+        io::stdout().flush().expect("failed to flush stdout");
+
+        if self.step {
+            // the echoed newline moves the cursor down one more line
+            io::stdin().lock().read_line(&mut String::new()).expect("failed to read stdin");
+            self.drawn += 1;
+        }
+        self.last = Some(Instant::now());
+    }
+
+    /// Restore the cursor; also done on drop
+    pub fn finish(&mut self) {
+        if self.tty && self.drawn > 0 {
+            print!("{}", SHOW_CURSOR);
+            io::stdout().flush().expect("failed to flush stdout");
+            self.drawn = 0;
+        }
+    }
+}
+impl Drop for Animator {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
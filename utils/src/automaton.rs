@@ -0,0 +1,211 @@
+use std::collections::BTreeSet;
+
+use crate::{Connectivity, Point};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum UpdateMode {
+    /// every cell sees the grid as it was at the start of the step
+    Synchronous,
+    /// cells are updated in place in row-major order, so later cells see earlier changes
+    Asynchronous,
+}
+
+/// A grid of cells updated by a rule over (own state, number of counted neighbours).
+///
+/// Neighbour counts are kept up to date incrementally, so a rule never has to look around itself.
+#[derive(Debug, Clone)]
+pub struct Automaton<S, P> {
+    cells: Vec<Vec<S>>,
+    counts: Vec<Vec<usize>>,
+    width: usize,
+    height: usize,
+    connectivity: Connectivity,
+    mode: UpdateMode,
+    counted: P,
+}
+impl<S, P> Automaton<S, P> where S: Copy + Eq, P: Fn(&S) -> bool {
+    /// `counted` decides which neighbours contribute to a cell's count
+    pub fn new(cells: Vec<Vec<S>>, connectivity: Connectivity, mode: UpdateMode, counted: P) -> Self {
+        let (width, height) = (cells.first().map_or(0, Vec::len), cells.len());
+        assert!(cells.iter().all(|r| r.len() == width), "all rows must have the same width");
+
+        let mut counts = vec![vec![0; width]; height];
+        for (y, row) in cells.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                if counted(cell) {
+                    for n in connectivity.neighbours(Point { x, y }, width, height) {
+                        counts[n.y][n.x] += 1;
+                    }
+                }
+            }
+        }
+
+        Self { cells, counts, width, height, connectivity, mode, counted }
+    }
+
+    pub fn cells(&self) -> &Vec<Vec<S>> {
+        &self.cells
+    }
+
+    pub fn into_cells(self) -> Vec<Vec<S>> {
+        self.cells
+    }
+
+    pub fn get(&self, pt: Point<usize>) -> S {
+        self.cells[pt.y][pt.x]
+    }
+
+    /// Number of counted neighbours of `pt`
+    pub fn count_at(&self, pt: Point<usize>) -> usize {
+        self.counts[pt.y][pt.x]
+    }
+
+    pub fn mode(&self) -> UpdateMode {
+        self.mode
+    }
+
+    fn all_cells(&self) -> BTreeSet<(usize, usize)> {
+        (0..self.height).flat_map(|y| (0..self.width).map(move |x| (y, x))).collect()
+    }
+
+    fn neighbours(&self, (y, x): (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+        self.connectivity.neighbours(Point { x, y }, self.width, self.height).map(|n| (n.y, n.x))
+    }
+
+    /// Store a new state and fix up the counts around it
+    fn write(&mut self, (y, x): (usize, usize), state: S) {
+        let (was, is) = ((self.counted)(&self.cells[y][x]), (self.counted)(&state));
+        self.cells[y][x] = state;
+
+        if was != is {
+            for (ny, nx) in self.neighbours((y, x)).collect::<Vec<_>>() {
+                if is {
+                    self.counts[ny][nx] += 1;
+                } else {
+                    self.counts[ny][nx] -= 1;
+                }
+            }
+        }
+    }
+
+    /// Update the candidate cells once; returns the changed cells and the candidates for the
+    /// next step
+    fn step_cells<R>(&mut self, mut candidates: BTreeSet<(usize, usize)>, rule: &R) -> (usize, BTreeSet<(usize, usize)>)
+    where R: Fn(S, usize) -> S {
+        let mut changed = 0;
+        let mut next = BTreeSet::new();
+
+        match self.mode {
+            UpdateMode::Synchronous => {
+                let updates: Vec<((usize, usize), S)> = candidates.into_iter()
+                    .map(|(y, x)| ((y, x), rule(self.cells[y][x], self.counts[y][x])))
+                    .filter(|&((y, x), state)| state != self.cells[y][x])
+                    .collect();
+
+                for (pos, state) in updates {
+                    self.write(pos, state);
+                    changed += 1;
+                    next.insert(pos);
+                    next.extend(self.neighbours(pos));
+                }
+            }
+            UpdateMode::Asynchronous => {
+                while let Some(pos) = candidates.pop_first() {
+                    let (y, x) = pos;
+                    let state = rule(self.cells[y][x], self.counts[y][x]);
+                    if state == self.cells[y][x] {
+                        continue;
+                    }
+
+                    self.write(pos, state);
+                    changed += 1;
+                    // cells later in the scan get another look during this step, earlier ones in the next
+                    for n in self.neighbours(pos).chain([pos]) {
+                        if n > pos {
+                            candidates.insert(n);
+                        } else {
+                            next.insert(n);
+                        }
+                    }
+                }
+            }
+        }
+
+        (changed, next)
+    }
+
+    /// Update every cell once; returns the number of cells that changed
+    pub fn step<R>(&mut self, rule: &R) -> usize where R: Fn(S, usize) -> S {
+        self.step_cells(self.all_cells(), rule).0
+    }
+
+    /// Step until nothing changes; returns the number of changed cells in each step, not
+    /// counting the final step without changes.
+    ///
+    /// After the first step only cells next to a change are looked at again, which gives the
+    /// same result as rescanning the whole grid each time.
+    pub fn run_until_stable<R>(&mut self, rule: &R) -> Vec<usize> where R: Fn(S, usize) -> S {
+        let mut history = vec![];
+        let mut candidates = self.all_cells();
+
+        loop {
+            let (changed, next) = self.step_cells(candidates, rule);
+            if changed == 0 {
+                return history;
+            }
+            history.push(changed);
+            candidates = next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> Vec<Vec<bool>> {
+        input.lines().map(|l| l.chars().map(|c| c == '#').collect()).collect()
+    }
+
+    /// day04: rolls with fewer than four neighbouring rolls are removed
+    fn peel(alive: bool, count: usize) -> bool {
+        alive && count >= 4
+    }
+
+    const ROLLS: &str = "..##.####.\n###.#.#.##\n#####.#.##\n#.####..#.\n##.####.##\n.#######.#\n.#.#.#.###\n#.###.####\n.########.\n#.#.###.#.";
+
+    #[test]
+    fn asynchronous_matches_rescanning() {
+        let mut worklist = Automaton::new(parse(ROLLS), Connectivity::Eight, UpdateMode::Asynchronous, |&c| c);
+        let mut rescan = worklist.clone();
+
+        let history = worklist.run_until_stable(&peel);
+        let mut expected = vec![];
+        loop {
+            match rescan.step(&peel) {
+                0 => break,
+                n => expected.push(n),
+            }
+        }
+
+        assert_eq!(history, expected);
+        assert_eq!(history.iter().sum::<usize>(), 43);
+        assert_eq!(worklist.cells(), rescan.cells());
+    }
+
+    #[test]
+    fn synchronous_life() {
+        // a blinker flips between horizontal and vertical
+        let life = |alive: bool, count: usize| count == 3 || (alive && count == 2);
+        let mut blinker = Automaton::new(parse(".....\n.....\n.###.\n.....\n....."), Connectivity::Eight, UpdateMode::Synchronous, |&c| c);
+
+        assert_eq!(blinker.step(&life), 4);
+        assert_eq!(blinker.cells(), &parse(".....\n..#..\n..#..\n..#..\n....."));
+        assert_eq!(blinker.count_at(Point { x: 2, y: 2 }), 2);
+        assert_eq!(blinker.step(&life), 4);
+
+        // a block is already stable
+        let mut block = Automaton::new(parse("....\n.##.\n.##.\n...."), Connectivity::Eight, UpdateMode::Synchronous, |&c| c);
+        assert_eq!(block.run_until_stable(&life), Vec::<usize>::new());
+    }
+}
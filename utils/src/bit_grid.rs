@@ -0,0 +1,279 @@
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not};
+
+use bitvec::{slice::BitSlice, vec::BitVec};
+
+use crate::{Connectivity, Point};
+
+/// Copy of `row` moved by `dx` columns (positive towards higher indices), zero-filled
+pub fn shifted_row(row: &BitSlice, dx: isize) -> BitVec {
+    let mut out = row.to_bitvec();
+    let (len, by) = (out.len(), dx.unsigned_abs());
+    if by >= len {
+        out.fill(false);
+    } else if dx < 0 {
+        out.copy_within(by.., 0);
+        out[len - by..].fill(false);
+    } else {
+        out.copy_within(..len - by, by);
+        out[..by].fill(false);
+    }
+    out
+}
+
+/// A grid of booleans packed one bit per cell, one [`BitVec`] per row.
+///
+/// Binary operators work row by row on whole storage words; both sides must have the same
+/// dimensions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitGrid {
+    width: usize,
+    rows: Vec<BitVec>,
+}
+impl BitGrid {
+    /// All cells cleared
+    pub fn new(width: usize, height: usize) -> Self {
+        Self { width, rows: vec![BitVec::repeat(false, width); height] }
+    }
+
+    pub fn from_grid<V, F>(grid: &[Vec<V>], mut pred: F) -> Self where F: FnMut(&V) -> bool {
+        Self::from_rows(grid.iter().map(|row| row.iter().map(&mut pred).collect()).collect())
+    }
+
+    pub fn from_rows(rows: Vec<BitVec>) -> Self {
+        let width = rows.first().map_or(0, BitVec::len);
+        assert!(rows.iter().all(|r| r.len() == width), "all rows must have the same width");
+        Self { width, rows }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn get(&self, pt: Point<usize>) -> bool {
+        self.rows[pt.y][pt.x]
+    }
+
+    pub fn set(&mut self, pt: Point<usize>, value: bool) {
+        self.rows[pt.y].set(pt.x, value);
+    }
+
+    pub fn row(&self, y: usize) -> &BitSlice {
+        &self.rows[y]
+    }
+
+    pub fn row_mut(&mut self, y: usize) -> &mut BitSlice {
+        &mut self.rows[y]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &BitSlice> {
+        self.rows.iter().map(BitVec::as_bitslice)
+    }
+
+    /// Number of set cells
+    pub fn count_ones(&self) -> usize {
+        self.rows.iter().map(|r| r.count_ones()).sum()
+    }
+
+    /// All set cells, in row-major order
+    pub fn iter_ones(&self) -> impl Iterator<Item = Point<usize>> + '_ {
+        self.rows.iter().enumerate().flat_map(|(y, r)| r.iter_ones().map(move |x| Point { x, y }))
+    }
+
+    /// Copy moved by (dx, dy): cell (x, y) of the result is cell (x - dx, y - dy) of `self`.
+    /// Cells shifted in from outside are cleared.
+    pub fn shifted(&self, dx: isize, dy: isize) -> Self {
+        let height = self.height();
+        let rows = (0..height).map(|y| {
+            match y.checked_add_signed(-dy).filter(|&src| src < height) {
+                Some(src) => shifted_row(&self.rows[src], dx),
+                None => BitVec::repeat(false, self.width),
+            }
+        }).collect();
+
+        Self { width: self.width, rows }
+    }
+
+    /// Number of set neighbours of every cell, computed for all cells at once
+    pub fn neighbour_counts(&self, connectivity: Connectivity) -> NeighbourCounts {
+        let mut counts = NeighbourCounts {
+            planes: vec![Self::new(self.width, self.height()); NeighbourCounts::PLANES],
+        };
+
+        for &(dx, dy) in connectivity.offsets() {
+            // the neighbour at (x + dx, y + dy) lands on (x, y)
+            counts.add(self.shifted(-dx, -dy));
+        }
+
+        counts
+    }
+
+    fn zip_rows<F>(&mut self, other: &Self, mut f: F) where F: FnMut(&mut BitVec, &BitVec) {
+        assert_eq!((self.width, self.height()), (other.width, other.height()), "grid dimensions differ");
+        self.rows.iter_mut().zip(&other.rows).for_each(|(a, b)| f(a, b));
+    }
+}
+
+impl BitAndAssign<&BitGrid> for BitGrid {
+    fn bitand_assign(&mut self, rhs: &BitGrid) {
+        self.zip_rows(rhs, |a, b| *a &= b);
+    }
+}
+impl BitOrAssign<&BitGrid> for BitGrid {
+    fn bitor_assign(&mut self, rhs: &BitGrid) {
+        self.zip_rows(rhs, |a, b| *a |= b);
+    }
+}
+impl BitXorAssign<&BitGrid> for BitGrid {
+    fn bitxor_assign(&mut self, rhs: &BitGrid) {
+        self.zip_rows(rhs, |a, b| *a ^= b);
+    }
+}
+impl BitAnd for &BitGrid {
+    type Output = BitGrid;
+
+    fn bitand(self, rhs: Self) -> BitGrid {
+        let mut out = self.clone();
+        out &= rhs;
+        out
+    }
+}
+impl BitOr for &BitGrid {
+    type Output = BitGrid;
+
+    fn bitor(self, rhs: Self) -> BitGrid {
+        let mut out = self.clone();
+        out |= rhs;
+        out
+    }
+}
+impl BitXor for &BitGrid {
+    type Output = BitGrid;
+
+    fn bitxor(self, rhs: Self) -> BitGrid {
+        let mut out = self.clone();
+        out ^= rhs;
+        out
+    }
+}
+impl Not for &BitGrid {
+    type Output = BitGrid;
+
+    fn not(self) -> BitGrid {
+        BitGrid { width: self.width, rows: self.rows.iter().map(|r| !r.clone()).collect() }
+    }
+}
+impl Not for BitGrid {
+    type Output = BitGrid;
+
+    fn not(self) -> BitGrid {
+        !&self
+    }
+}
+
+/// Per-cell counts stored bit-sliced: plane i holds bit i of every cell's count
+#[derive(Debug, Clone)]
+pub struct NeighbourCounts {
+    planes: Vec<BitGrid>,
+}
+impl NeighbourCounts {
+    /// enough for counts up to 15
+    const PLANES: usize = 4;
+
+    /// Add one bit to every cell's count with a chain of half adders
+    fn add(&mut self, bits: BitGrid) {
+        let mut carry = bits;
+        for plane in &mut self.planes {
+            let next = &*plane & &carry;
+            *plane ^= &carry;
+            carry = next;
+        }
+        debug_assert_eq!(carry.count_ones(), 0, "neighbour count overflow");
+    }
+
+    pub fn count_at(&self, pt: Point<usize>) -> usize {
+        self.planes.iter().enumerate().map(|(i, plane)| (plane.get(pt) as usize) << i).sum()
+    }
+
+    /// Cells whose count is below `k`, via a bit-sliced comparison from the top plane down
+    pub fn below(&self, k: usize) -> BitGrid {
+        let (width, height) = (self.planes[0].width(), self.planes[0].height());
+        if k >> Self::PLANES != 0 {
+            return !BitGrid::new(width, height);
+        }
+
+        let mut less = BitGrid::new(width, height);
+        let mut equal = !&less;
+        for (i, plane) in self.planes.iter().enumerate().rev() {
+            if k >> i & 1 == 1 {
+                less |= &(&equal & &!plane);
+                equal &= plane;
+            } else {
+                equal &= &!plane;
+            }
+        }
+
+        less
+    }
+
+    /// Cells whose count is at least `k`
+    pub fn at_least(&self, k: usize) -> BitGrid {
+        !self.below(k)
+    }
+
+    /// Cells whose count is exactly `k`
+    pub fn equal(&self, k: usize) -> BitGrid {
+        &self.at_least(k) & &self.below(k + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(input: &str) -> BitGrid {
+        let chars: Vec<Vec<char>> = input.lines().map(|l| l.chars().collect()).collect();
+        BitGrid::from_grid(&chars, |&c| c == '#')
+    }
+
+    #[test]
+    fn shifts_and_ops() {
+        let g = grid("#..\n.#.\n..#");
+        assert_eq!(g.count_ones(), 3);
+        assert_eq!(g.shifted(1, 0), grid(".#.\n..#\n..."));
+        assert_eq!(g.shifted(-1, 1), grid("...\n...\n#.."));
+        assert_eq!(g.shifted(0, -5), BitGrid::new(3, 3));
+        assert_eq!(g.shifted(3, 0), BitGrid::new(3, 3));
+        assert_eq!(g.shifted(-7, 0), BitGrid::new(3, 3));
+
+        let h = grid("###\n...\n...");
+        assert_eq!(&g & &h, grid("#..\n...\n..."));
+        assert_eq!(&g | &h, grid("###\n.#.\n..#"));
+        assert_eq!(&g ^ &h, grid(".##\n.#.\n..#"));
+        assert_eq!((!&g).count_ones(), 6);
+        assert_eq!(g.iter_ones().collect::<Vec<_>>(), vec![Point { x: 0, y: 0 }, Point { x: 1, y: 1 }, Point { x: 2, y: 2 }]);
+    }
+
+    #[test]
+    fn neighbour_counts() {
+        let g = grid("###.\n#.#.\n###.\n....");
+        let counts = g.neighbour_counts(Connectivity::Eight);
+        let four = g.neighbour_counts(Connectivity::Four);
+
+        for pt in (0..4).flat_map(|y| (0..4).map(move |x| Point { x, y })) {
+            let brute = Connectivity::Eight.neighbours(pt, 4, 4).filter(|&n| g.get(n)).count();
+            assert_eq!(counts.count_at(pt), brute, "{}", pt);
+            let brute = Connectivity::Four.neighbours(pt, 4, 4).filter(|&n| g.get(n)).count();
+            assert_eq!(four.count_at(pt), brute, "{}", pt);
+
+            for k in 0..10 {
+                assert_eq!(counts.at_least(k).get(pt), counts.count_at(pt) >= k);
+                assert_eq!(counts.equal(k).get(pt), counts.count_at(pt) == k);
+            }
+        }
+        assert_eq!(counts.count_at(Point { x: 1, y: 1 }), 8);
+    }
+}
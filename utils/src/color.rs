@@ -0,0 +1,270 @@
+/// An sRGB colour, 8 bits per channel.
+///
+/// Hues are in degrees and wrap around, so -30 and 330 are the same hue. Saturation, lightness
+/// and value are fractions in 0..=1 and are clamped to that range.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8
+}
+impl Color {
+    pub const WHITE: Color = Color::rgb(255, 255, 255);
+    pub const BLACK: Color = Color::rgb(0, 0, 0);
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Color {
+        Color { r, g, b }
+    }
+
+    /// From hue, chroma and the amount `m` added to every channel
+    fn from_chroma(h: f32, c: f32, m: f32) -> Color {
+        let h_prime = h.rem_euclid(360.0) / 60.0;
+        let x = c * (1.0 - (h_prime % 2.0 - 1.0).abs());
+
+        // rem_euclid can round up to exactly 360 for tiny negative hues
+        let (r1, g1, b1) = match (h_prime as u32).min(5) {
+            0 => (c, x, 0.0),
+            1 => (x, c, 0.0),
+            2 => (0.0, c, x),
+            3 => (0.0, x, c),
+            4 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+
+        let channel = |v: f32| ((v + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+        Color::rgb(channel(r1), channel(g1), channel(b1))
+    }
+
+    /// Channels as fractions, with the largest and smallest of them
+    fn unit(self) -> ([f32; 3], f32, f32) {
+        let rgb = [self.r, self.g, self.b].map(|v| v as f32 / 255.0);
+        (rgb, rgb[0].max(rgb[1]).max(rgb[2]), rgb[0].min(rgb[1]).min(rgb[2]))
+    }
+
+    /// Hue in 0..360 shared by HSL and HSV, 0 for greys
+    fn hue(self) -> f32 {
+        let ([r, g, b], max, min) = self.unit();
+        let d = max - min;
+        if d == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / d).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / d + 2.0)
+        } else {
+            60.0 * ((r - g) / d + 4.0)
+        }
+    }
+
+    pub fn hsl(h: f32, s: f32, l: f32) -> Color {
+        let (s, l) = (s.clamp(0.0, 1.0), l.clamp(0.0, 1.0));
+        let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        Self::from_chroma(h, c, l - c / 2.0)
+    }
+
+    /// Hue, saturation and lightness
+    pub fn to_hsl(self) -> (f32, f32, f32) {
+        let (_, max, min) = self.unit();
+        let l = (max + min) / 2.0;
+        let s = if max == min { 0.0 } else { (max - min) / (1.0 - (2.0 * l - 1.0).abs()) };
+        (self.hue(), s, l)
+    }
+
+    pub fn hsv(h: f32, s: f32, v: f32) -> Color {
+        let (s, v) = (s.clamp(0.0, 1.0), v.clamp(0.0, 1.0));
+        let c = v * s;
+        Self::from_chroma(h, c, v - c)
+    }
+
+    /// Hue, saturation and value
+    pub fn to_hsv(self) -> (f32, f32, f32) {
+        let (_, max, min) = self.unit();
+        let s = if max == 0.0 { 0.0 } else { (max - min) / max };
+        (self.hue(), s, max)
+    }
+
+    /// From Oklab lightness and a/b opponent axes, clipping colours outside sRGB
+    pub fn oklab(l: f32, a: f32, b: f32) -> Color {
+        let (l, a, b) = (l as f64, a as f64, b as f64);
+        let l_ = (l + 0.396_337_777_4 * a + 0.215_803_757_3 * b).powi(3);
+        let m_ = (l - 0.105_561_345_8 * a - 0.063_854_172_8 * b).powi(3);
+        let s_ = (l - 0.089_484_177_5 * a - 1.291_485_548_0 * b).powi(3);
+
+        let channel = |v: f64| {
+            let v = v.clamp(0.0, 1.0);
+            let encoded = if v <= 0.003_130_8 { 12.92 * v } else { 1.055 * v.powf(1.0 / 2.4) - 0.055 };
+            (encoded * 255.0).round() as u8
+        };
+        Color::rgb(
+            channel(4.076_741_662_1 * l_ - 3.307_711_591_3 * m_ + 0.230_969_929_2 * s_),
+            channel(-1.268_438_004_6 * l_ + 2.609_757_401_1 * m_ - 0.341_319_396_5 * s_),
+            channel(-0.004_196_086_3 * l_ - 0.703_418_614_7 * m_ + 1.707_614_701_0 * s_),
+        )
+    }
+
+    /// Oklab lightness (0..=1) and a/b opponent axes
+    pub fn to_oklab(self) -> (f32, f32, f32) {
+        let [r, g, b] = self.linear();
+        let l = (0.412_221_470_8 * r + 0.536_332_536_3 * g + 0.051_445_992_9 * b).cbrt();
+        let m = (0.211_903_498_2 * r + 0.680_699_545_1 * g + 0.107_396_956_6 * b).cbrt();
+        let s = (0.088_302_461_9 * r + 0.281_718_837_6 * g + 0.629_978_700_5 * b).cbrt();
+
+        (
+            (0.210_454_255_3 * l + 0.793_617_785_0 * m - 0.004_072_046_8 * s) as f32,
+            (1.977_998_495_1 * l - 2.428_592_205_0 * m + 0.450_593_709_9 * s) as f32,
+            (0.025_904_037_1 * l + 0.782_771_766_2 * m - 0.808_675_766_0 * s) as f32,
+        )
+    }
+
+    /// Channels with the sRGB transfer curve undone
+    fn linear(self) -> [f64; 3] {
+        [self.r, self.g, self.b].map(|v| {
+            let v = v as f64 / 255.0;
+            if v <= 0.040_45 { v / 12.92 } else { ((v + 0.055) / 1.055).powf(2.4) }
+        })
+    }
+
+    /// Parse `#rrggbb`, in either case
+    pub fn from_hex(hex: &str) -> Option<Color> {
+        let digits = hex.strip_prefix('#')?;
+        if digits.len() != 6 || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        let channel = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).ok();
+        Some(Color::rgb(channel(0)?, channel(2)?, channel(4)?))
+    }
+
+    /// `#rrggbb` in lower case
+    pub fn to_hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+
+    /// `t` of the way from `self` to `other`, per sRGB channel
+    pub fn blend(self, other: Color, t: f32) -> Color {
+        let t = t.clamp(0.0, 1.0);
+        let channel = |x: u8, y: u8| (x as f32 + (y as f32 - x as f32) * t).round() as u8;
+        Color::rgb(channel(self.r, other.r), channel(self.g, other.g), channel(self.b, other.b))
+    }
+
+    /// WCAG relative luminance, 0 for black to 1 for white
+    pub fn luminance(self) -> f32 {
+        let [r, g, b] = self.linear();
+        (0.2126 * r + 0.7152 * g + 0.0722 * b) as f32
+    }
+
+    /// WCAG contrast ratio, from 1 (identical luminance) to 21 (black on white)
+    pub fn contrast_ratio(self, other: Color) -> f32 {
+        let (a, b) = (self.luminance(), other.luminance());
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+    /// Black or white, whichever reads better on top of `self`
+    pub fn text_color(self) -> Color {
+        if self.contrast_ratio(Color::BLACK) >= self.contrast_ratio(Color::WHITE) { Color::BLACK } else { Color::WHITE }
+    }
+
+    pub fn random_from_seed(seed: usize) -> Color {
+        // multiply by a large prime to simulate randomness
+        let seed = seed.wrapping_mul(10722542609);
+
+        let hue = (seed % 360) as f32;
+        let saturation = 0.9;
+        let lightness = 0.6;
+
+        Color::hsl(hue, saturation, lightness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every colour on a lattice with `step` between channel values, including 255
+    fn lattice(step: usize) -> Vec<Color> {
+        let mut values: Vec<u8> = (0..=255).step_by(step).collect();
+        if values.last() != Some(&255) {
+            values.push(255);
+        }
+        let mut colors = vec![];
+        for &r in &values {
+            for &g in &values {
+                colors.extend(values.iter().map(|&b| Color::rgb(r, g, b)));
+            }
+        }
+        colors
+    }
+
+    #[test]
+    fn hue_wraparound() {
+        let red = Color::rgb(255, 0, 0);
+        assert_eq!(Color::hsl(0.0, 1.0, 0.5), red);
+        assert_eq!(Color::hsl(360.0, 1.0, 0.5), red);
+        assert_eq!(Color::hsl(-360.0, 1.0, 0.5), red);
+        assert_eq!(Color::hsl(-1e-6, 1.0, 0.5), red);
+        assert_eq!(Color::hsl(120.0, 1.0, 0.5), Color::rgb(0, 255, 0));
+        assert_eq!(Color::hsl(240.0, 1.0, 0.25), Color::rgb(0, 0, 128));
+        assert_eq!(Color::hsv(300.0, 1.0, 1.0), Color::rgb(255, 0, 255));
+        assert_eq!(Color::hsl(0.0, 2.0, 1.5), Color::WHITE);
+
+        for h in (0..360).step_by(7).map(|h| h as f32 + 0.5) {
+            let c = Color::hsl(h, 0.8, 0.4);
+            assert_eq!(Color::hsl(h + 360.0, 0.8, 0.4), c, "{}", h);
+            assert_eq!(Color::hsl(h - 720.0, 0.8, 0.4), c, "{}", h);
+            assert_eq!(Color::hsv(h - 360.0, 0.8, 0.4), Color::hsv(h, 0.8, 0.4), "{}", h);
+        }
+
+        // only a hue of exactly 360 used to fall through to black
+        assert!((0..10_000).map(Color::random_from_seed).all(|c| c != Color::BLACK));
+    }
+
+    #[test]
+    fn round_trips() {
+        for c in lattice(5) {
+            let (h, s, l) = c.to_hsl();
+            assert!((0.0..360.0).contains(&h), "{:?}", c);
+            assert_eq!(Color::hsl(h, s, l), c);
+
+            let (h, s, v) = c.to_hsv();
+            assert_eq!(Color::hsv(h, s, v), c);
+
+            let (l, a, b) = c.to_oklab();
+            assert_eq!(Color::oklab(l, a, b), c);
+
+            assert_eq!(Color::from_hex(&c.to_hex()), Some(c));
+        }
+
+        let (l, a, b) = Color::WHITE.to_oklab();
+        assert!((l - 1.0).abs() < 1e-4 && a.abs() < 1e-4 && b.abs() < 1e-4);
+        let (l, a, b) = Color::rgb(255, 0, 0).to_oklab();
+        assert!((l - 0.628).abs() < 1e-3 && (a - 0.2249).abs() < 1e-3 && (b - 0.1258).abs() < 1e-3);
+    }
+
+    #[test]
+    fn hex() {
+        assert_eq!(Color::from_hex("#1A2b3c"), Some(Color::rgb(0x1a, 0x2b, 0x3c)));
+        assert_eq!(Color::rgb(0x1a, 0x2b, 0x3c).to_hex(), "#1a2b3c");
+        for bad in ["1a2b3c", "#1a2b3", "#1a2b3c4", "#+a2b3c", "#1a2b3g", "#é2b3c"] {
+            assert_eq!(Color::from_hex(bad), None, "{}", bad);
+        }
+    }
+
+    #[test]
+    fn blending_and_contrast() {
+        let red = Color::rgb(255, 0, 0);
+        assert_eq!(Color::BLACK.blend(red, 0.0), Color::BLACK);
+        assert_eq!(Color::BLACK.blend(red, 1.0), red);
+        assert_eq!(Color::BLACK.blend(Color::WHITE, 0.5), Color::rgb(128, 128, 128));
+        assert_eq!(Color::BLACK.blend(red, 7.0), red);
+
+        assert!((Color::BLACK.contrast_ratio(Color::WHITE) - 21.0).abs() < 1e-3);
+        assert_eq!(red.contrast_ratio(red), 1.0);
+        assert_eq!(Color::rgb(0xfd, 0xe7, 0x25).text_color(), Color::BLACK);
+        assert_eq!(Color::rgb(0x44, 0x01, 0x54).text_color(), Color::WHITE);
+
+        // the better of black and white always meets WCAG AA for normal text
+        for c in lattice(15) {
+            assert!(c.contrast_ratio(c.text_color()) >= 4.5, "{:?}", c);
+        }
+    }
+}
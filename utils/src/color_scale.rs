@@ -0,0 +1,164 @@
+use crate::{Color, Style, StyledChar};
+
+/// How values are spread over a scale
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Mapping {
+    Linear,
+    /// by ln(1 + v - min), for counts that span orders of magnitude
+    Log,
+}
+
+/// Samples of matplotlib's viridis, from dark purple to yellow
+const VIRIDIS: [Color; 10] = [
+    Color::rgb(0x44, 0x01, 0x54), Color::rgb(0x48, 0x28, 0x78), Color::rgb(0x3e, 0x49, 0x89), Color::rgb(0x31, 0x68, 0x8e),
+    Color::rgb(0x26, 0x82, 0x8e), Color::rgb(0x1f, 0x9e, 0x89), Color::rgb(0x35, 0xb7, 0x79), Color::rgb(0x6e, 0xce, 0x58),
+    Color::rgb(0xb5, 0xde, 0x2b), Color::rgb(0xfd, 0xe7, 0x25),
+];
+
+/// Blue through light grey to red
+const COOLWARM: [Color; 3] = [Color::rgb(0x3b, 0x4c, 0xc0), Color::rgb(0xf7, 0xf7, 0xf7), Color::rgb(0xb4, 0x04, 0x26)];
+
+/// Maps numbers in `min..=max` onto a gradient through evenly spaced colour stops
+#[derive(Clone)]
+pub struct ColorScale {
+    stops: Vec<Color>,
+    min: f64,
+    max: f64,
+    /// value placed at the middle of the gradient, for diverging scales
+    center: Option<f64>,
+    mapping: Mapping,
+}
+impl ColorScale {
+    pub fn new(stops: Vec<Color>, min: f64, max: f64) -> Self {
+        assert!(!stops.is_empty(), "a scale needs at least one colour");
+        Self { stops, min, max, center: None, mapping: Mapping::Linear }
+    }
+
+    pub fn viridis(min: f64, max: f64) -> Self {
+        Self::new(VIRIDIS.to_vec(), min, max)
+    }
+
+    pub fn greys(min: f64, max: f64) -> Self {
+        Self::new(vec![Color::BLACK, Color::WHITE], min, max)
+    }
+
+    /// Blue below `center`, red above, light grey at `center`
+    pub fn diverging(min: f64, center: f64, max: f64) -> Self {
+        Self { center: Some(center), ..Self::new(COOLWARM.to_vec(), min, max) }
+    }
+
+    pub fn with_mapping(mut self, mapping: Mapping) -> Self {
+        self.mapping = mapping;
+        self
+    }
+
+    pub fn min(&self) -> f64 {
+        self.min
+    }
+
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+
+    fn stretch(&self, v: f64) -> f64 {
+        match self.mapping {
+            Mapping::Linear => v,
+            Mapping::Log => (1.0 + (v - self.min).max(0.0)).ln(),
+        }
+    }
+
+    /// Position of `v` along the gradient, clamped to 0..=1
+    pub fn normalize(&self, v: f64) -> f64 {
+        let fraction = |lo: f64, hi: f64| {
+            let (lo, hi, v) = (self.stretch(lo), self.stretch(hi), self.stretch(v));
+            if hi > lo { ((v - lo) / (hi - lo)).clamp(0.0, 1.0) } else { 0.0 }
+        };
+
+        match self.center {
+            Some(c) if v < c => 0.5 * fraction(self.min, c),
+            Some(c) => 0.5 + 0.5 * fraction(c, self.max),
+            None => fraction(self.min, self.max),
+        }
+    }
+
+    /// Colour at position `t` (0..=1) of the gradient
+    pub fn at(&self, t: f64) -> Color {
+        let segments = self.stops.len() - 1;
+        if segments == 0 {
+            return self.stops[0];
+        }
+
+        let pos = t.clamp(0.0, 1.0) * segments as f64;
+        let i = (pos.floor() as usize).min(segments - 1);
+        self.stops[i].blend(self.stops[i + 1], (pos - i as f64) as f32)
+    }
+
+    pub fn color(&self, v: f64) -> Color {
+        self.at(self.normalize(v))
+    }
+}
+
+/// Styled cells for a numeric grid. `cell` gives the character to show and the value to colour
+/// the background by; cells without a value are left unstyled.
+pub fn heatmap<T, F>(grid: &[Vec<T>], scale: &ColorScale, cell: F) -> Vec<Vec<StyledChar>> where F: Fn(&T) -> (char, Option<f64>) {
+    grid.iter().map(|row| row.iter().map(|v| {
+        let (chr, value) = cell(v);
+        match value {
+            Some(value) => {
+                let bg = scale.color(value);
+                StyledChar { chr, style: Style { fg: Some(bg.text_color()), bg: Some(bg), ..Default::default() } }
+            }
+            None => StyledChar::of(chr),
+        }
+    }).collect()).collect()
+}
+
+/// A one-line key: `min`, a `width` cell gradient, then `max`
+pub fn legend(scale: &ColorScale, width: usize) -> Vec<StyledChar> {
+    let mut row: Vec<StyledChar> = format!("{} ", scale.min()).chars().map(StyledChar::of).collect();
+    row.extend((0..width).map(|i| {
+        let t = if width > 1 { i as f64 / (width - 1) as f64 } else { 0.0 };
+        StyledChar { chr: ' ', style: Style::bg(Some(scale.at(t))) }
+    }));
+    row.extend(format!(" {}", scale.max()).chars().map(StyledChar::of));
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scales() {
+        let viridis = ColorScale::viridis(0.0, 9.0);
+        assert!(viridis.color(-5.0) == VIRIDIS[0]);
+        assert!(viridis.color(4.0) == VIRIDIS[4]);
+        assert!(viridis.color(9.0) == VIRIDIS[9]);
+        assert!(viridis.at(0.5) == VIRIDIS[4].blend(VIRIDIS[5], 0.5));
+
+        let log = ColorScale::greys(0.0, 99.0).with_mapping(Mapping::Log);
+        assert!((log.normalize(9.0) - 0.5).abs() < 1e-9);
+        assert!(log.color(99.0) == Color::WHITE);
+
+        let diverging = ColorScale::diverging(-10.0, 0.0, 100.0);
+        assert_eq!(diverging.normalize(-5.0), 0.25);
+        assert_eq!(diverging.normalize(50.0), 0.75);
+        assert!(diverging.color(0.0) == COOLWARM[1]);
+    }
+
+    #[test]
+    fn heatmap_cells() {
+        let grid = vec![vec![0, 5, -1]];
+        let scale = ColorScale::greys(0.0, 10.0);
+        let cells = heatmap(&grid, &scale, |&v| if v < 0 { ('.', None) } else { (char::from_digit(v as u32, 10).unwrap(), Some(v as f64)) });
+
+        assert_eq!(cells[0].iter().map(|c| c.chr).collect::<String>(), "05.");
+        assert!(cells[0][0].style == Style { fg: Some(Color::WHITE), bg: Some(Color::BLACK), ..Default::default() });
+        assert!(cells[0][1].style.bg == Some(Color::rgb(128, 128, 128)));
+        assert!(cells[0][2].style == Style::default());
+
+        let key = legend(&scale, 3);
+        assert_eq!(key.iter().map(|c| c.chr).collect::<String>(), "0     10");
+        assert!(key[2].style.bg == Some(Color::BLACK) && key[4].style.bg == Some(Color::WHITE));
+    }
+}
@@ -0,0 +1,298 @@
+use std::ops::RangeInclusive;
+
+use crate::{Point, Point3};
+
+/// Coordinate compression along a single axis.
+///
+/// With sorted keys k_0 < k_1 < ... < k_(n-1), key k_i maps to cell 2i and the values strictly
+/// between k_i and k_(i+1) map to cell 2i + 1, so every value in k_0..=k_(n-1) has a cell and the
+/// cells keep the shape of the original space.
+#[derive(Debug, Clone)]
+pub struct AxisCompactor<T> {
+    keys: Vec<T>,
+    frozen: bool,
+}
+impl<T> Default for AxisCompactor<T> where T: Ord + num_traits::Num + Copy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<T> AxisCompactor<T> where T: Ord + num_traits::Num + Copy {
+    pub fn new() -> Self {
+        Self { keys: vec![], frozen: true }
+    }
+
+    pub fn add_key(&mut self, key: T) {
+        self.keys.push(key);
+        self.frozen = false;
+    }
+
+    /// Sort and deduplicate the keys; must be called after adding keys and before compacting
+    pub fn freeze(&mut self) {
+        if !self.frozen {
+            self.keys.sort_unstable();
+            self.keys.dedup();
+            self.frozen = true;
+        }
+    }
+
+    fn assert_frozen(&self) {
+        assert!(self.frozen, "compactor must be frozen before use");
+    }
+
+    pub fn keys(&self) -> &[T] {
+        self.assert_frozen();
+        &self.keys
+    }
+
+    /// Number of compacted cells
+    pub fn len(&self) -> usize {
+        (2 * self.keys.len()).saturating_sub(1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn compact(&self, value: T) -> usize {
+        self.assert_frozen();
+        match self.keys.binary_search(&value) {
+            Ok(i) => 2 * i,
+            Err(i) => {
+                assert!(0 < i && i < self.keys.len(), "value outside of compacted range");
+                2 * i - 1
+            }
+        }
+    }
+
+    /// The first original value covered by `cell`
+    pub fn decompact(&self, cell: usize) -> T {
+        *self.span(cell).start()
+    }
+
+    /// All original values covered by `cell`. Empty for a gap between two adjacent keys.
+    pub fn span(&self, cell: usize) -> RangeInclusive<T> {
+        self.assert_frozen();
+        assert!(cell < self.len(), "cell out of range");
+
+        let i = cell / 2;
+        if cell.is_multiple_of(2) {
+            self.keys[i]..=self.keys[i]
+        } else {
+            (self.keys[i] + T::one())..=(self.keys[i + 1] - T::one())
+        }
+    }
+
+    /// Number of original values covered by `cell`
+    pub fn span_len(&self, cell: usize) -> T {
+        self.assert_frozen();
+        assert!(cell < self.len(), "cell out of range");
+
+        let i = cell / 2;
+        if cell.is_multiple_of(2) {
+            T::one()
+        } else {
+            self.keys[i + 1] - self.keys[i] - T::one()
+        }
+    }
+}
+impl<T> FromIterator<T> for AxisCompactor<T> where T: Ord + num_traits::Num + Copy {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut compactor = Self::new();
+        iter.into_iter().for_each(|k| compactor.add_key(k));
+        compactor.freeze();
+        compactor
+    }
+}
+
+/// Coordinate compression of 2D points, one [`AxisCompactor`] per axis
+#[derive(Debug, Clone)]
+pub struct Compactor<T> {
+    xs: AxisCompactor<T>,
+    ys: AxisCompactor<T>
+}
+impl<T> Default for Compactor<T> where T: Ord + num_traits::Num + Copy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<T> Compactor<T> where T: Ord + num_traits::Num + Copy {
+    pub fn new() -> Self {
+        Self { xs: AxisCompactor::new(), ys: AxisCompactor::new() }
+    }
+
+    pub fn add_key_point(&mut self, pt: Point<T>) {
+        self.xs.add_key(pt.x);
+        self.ys.add_key(pt.y);
+    }
+
+    pub fn freeze(&mut self) {
+        self.xs.freeze();
+        self.ys.freeze();
+    }
+
+    pub fn xs(&self) -> &AxisCompactor<T> {
+        &self.xs
+    }
+
+    pub fn ys(&self) -> &AxisCompactor<T> {
+        &self.ys
+    }
+
+    /// Number of compacted columns
+    pub fn width(&self) -> usize {
+        self.xs.len()
+    }
+
+    /// Number of compacted rows
+    pub fn height(&self) -> usize {
+        self.ys.len()
+    }
+
+    pub fn compact(&self, pt: Point<T>) -> Point<usize> {
+        Point { x: self.xs.compact(pt.x), y: self.ys.compact(pt.y) }
+    }
+
+    pub fn decompact(&self, cell: Point<usize>) -> Point<T> {
+        Point { x: self.xs.decompact(cell.x), y: self.ys.decompact(cell.y) }
+    }
+
+    /// Number of original points covered by `cell`
+    pub fn cell_area(&self, cell: Point<usize>) -> T {
+        self.xs.span_len(cell.x) * self.ys.span_len(cell.y)
+    }
+}
+impl<T> FromIterator<Point<T>> for Compactor<T> where T: Ord + num_traits::Num + Copy {
+    fn from_iter<I: IntoIterator<Item = Point<T>>>(iter: I) -> Self {
+        let mut compactor = Self::new();
+        iter.into_iter().for_each(|pt| compactor.add_key_point(pt));
+        compactor.freeze();
+        compactor
+    }
+}
+
+/// Coordinate compression of 3D points, one [`AxisCompactor`] per axis
+#[derive(Debug, Clone)]
+pub struct Compactor3<T> {
+    xs: AxisCompactor<T>,
+    ys: AxisCompactor<T>,
+    zs: AxisCompactor<T>
+}
+impl<T> Default for Compactor3<T> where T: Ord + num_traits::Num + Copy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<T> Compactor3<T> where T: Ord + num_traits::Num + Copy {
+    pub fn new() -> Self {
+        Self { xs: AxisCompactor::new(), ys: AxisCompactor::new(), zs: AxisCompactor::new() }
+    }
+
+    pub fn add_key_point(&mut self, pt: Point3<T>) {
+        self.xs.add_key(pt.x);
+        self.ys.add_key(pt.y);
+        self.zs.add_key(pt.z);
+    }
+
+    pub fn freeze(&mut self) {
+        self.xs.freeze();
+        self.ys.freeze();
+        self.zs.freeze();
+    }
+
+    pub fn xs(&self) -> &AxisCompactor<T> {
+        &self.xs
+    }
+
+    pub fn ys(&self) -> &AxisCompactor<T> {
+        &self.ys
+    }
+
+    pub fn zs(&self) -> &AxisCompactor<T> {
+        &self.zs
+    }
+
+    pub fn compact(&self, pt: Point3<T>) -> Point3<usize> {
+        Point3 { x: self.xs.compact(pt.x), y: self.ys.compact(pt.y), z: self.zs.compact(pt.z) }
+    }
+
+    pub fn decompact(&self, cell: Point3<usize>) -> Point3<T> {
+        Point3 { x: self.xs.decompact(cell.x), y: self.ys.decompact(cell.y), z: self.zs.decompact(cell.z) }
+    }
+
+    /// Number of original points covered by `cell`
+    pub fn cell_volume(&self, cell: Point3<usize>) -> T {
+        self.xs.span_len(cell.x) * self.ys.span_len(cell.y) * self.zs.span_len(cell.z)
+    }
+}
+impl<T> FromIterator<Point3<T>> for Compactor3<T> where T: Ord + num_traits::Num + Copy {
+    fn from_iter<I: IntoIterator<Item = Point3<T>>>(iter: I) -> Self {
+        let mut compactor = Self::new();
+        iter.into_iter().for_each(|pt| compactor.add_key_point(pt));
+        compactor.freeze();
+        compactor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn axis() {
+        let axis: AxisCompactor<u32> = [10, 3, 4, 10, 7].into_iter().collect();
+        assert_eq!(axis.keys(), &[3, 4, 7, 10]);
+        assert_eq!(axis.len(), 7);
+
+        assert_eq!(axis.compact(3), 0);
+        assert_eq!(axis.compact(4), 2);
+        assert_eq!(axis.compact(5), 3);
+        assert_eq!(axis.compact(6), 3);
+        assert_eq!(axis.compact(10), 6);
+
+        assert!(axis.span(1).is_empty());
+        assert_eq!(axis.span(5), 8..=9);
+        assert_eq!(axis.decompact(4), 7);
+        assert_eq!(axis.decompact(5), 8);
+
+        let lens: Vec<u32> = (0..axis.len()).map(|c| axis.span_len(c)).collect();
+        assert_eq!(lens, vec![1, 0, 1, 2, 1, 2, 1]);
+        assert_eq!(lens.iter().sum::<u32>(), 10 - 3 + 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "outside of compacted range")]
+    fn axis_out_of_range() {
+        let axis: AxisCompactor<u32> = [3, 7].into_iter().collect();
+        axis.compact(8);
+    }
+
+    #[test]
+    #[should_panic(expected = "cell out of range")]
+    fn span_len_out_of_range() {
+        let axis: AxisCompactor<u32> = [3, 7].into_iter().collect();
+        axis.span_len(axis.len());
+    }
+
+    #[test]
+    #[should_panic(expected = "frozen")]
+    fn unfrozen() {
+        let mut axis = AxisCompactor::new();
+        axis.add_key(1);
+        axis.compact(1);
+    }
+
+    #[test]
+    fn planar_and_spatial() {
+        let compactor: Compactor<usize> = [Point { x: 2, y: 5 }, Point { x: 9, y: 1 }].into_iter().collect();
+        assert_eq!((compactor.width(), compactor.height()), (3, 3));
+        assert_eq!(compactor.compact(Point { x: 9, y: 5 }), Point { x: 2, y: 2 });
+        assert_eq!(compactor.decompact(Point { x: 1, y: 1 }), Point { x: 3, y: 2 });
+        assert_eq!(compactor.cell_area(Point { x: 1, y: 1 }), 6 * 3);
+
+        let compactor3: Compactor3<i64> = [Point3 { x: 0, y: 0, z: -4 }, Point3 { x: 5, y: 1, z: 4 }].into_iter().collect();
+        assert_eq!(compactor3.compact(Point3 { x: 3, y: 1, z: 0 }), Point3 { x: 1, y: 2, z: 1 });
+        assert_eq!(compactor3.cell_volume(Point3 { x: 1, y: 1, z: 1 }), 0);
+        assert_eq!(compactor3.cell_volume(Point3 { x: 1, y: 2, z: 1 }), 4 * 7);
+    }
+}
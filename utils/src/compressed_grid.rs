@@ -0,0 +1,112 @@
+use num_traits::PrimInt;
+
+use crate::{Compactor, Point, Rect};
+
+/// A [`Compactor`] together with the real area of every compacted cell that belongs to some
+/// shape, and 2D prefix sums over those areas.
+///
+/// Answers "how many original points of this rectangle are inside the shape" in O(1).
+#[derive(Debug, Clone)]
+pub struct CompressedGrid<T> {
+    compactor: Compactor<T>,
+    /// weights[y][x] is the real area of cell (x, y) if it is inside, otherwise zero
+    weights: Vec<Vec<T>>,
+    /// prefix[y][x] is the sum of weights[..y][..x]
+    prefix: Vec<Vec<T>>,
+}
+impl<T> CompressedGrid<T> where T: PrimInt {
+    /// `inside` is asked once for every compacted cell
+    pub fn new<F>(compactor: Compactor<T>, mut inside: F) -> Self where F: FnMut(Point<usize>) -> bool {
+        let (width, height) = (compactor.width(), compactor.height());
+
+        let weights: Vec<Vec<T>> = (0..height).map(|y| (0..width).map(|x| {
+            let cell = Point { x, y };
+            if inside(cell) { compactor.cell_area(cell) } else { T::zero() }
+        }).collect()).collect();
+
+        let mut prefix = vec![vec![T::zero(); width + 1]; height + 1];
+        for y in 0..height {
+            for x in 0..width {
+                prefix[y + 1][x + 1] = weights[y][x] + prefix[y][x + 1] + prefix[y + 1][x] - prefix[y][x];
+            }
+        }
+
+        Self { compactor, weights, prefix }
+    }
+
+    /// Build from a grid laid out in compacted coordinates
+    pub fn from_grid<V, F>(compactor: Compactor<T>, grid: &[Vec<V>], inside: F) -> Self where F: Fn(&V) -> bool {
+        Self::new(compactor, |cell| inside(&grid[cell.y][cell.x]))
+    }
+
+    pub fn compactor(&self) -> &Compactor<T> {
+        &self.compactor
+    }
+
+    pub fn weight(&self, cell: Point<usize>) -> T {
+        self.weights[cell.y][cell.x]
+    }
+
+    /// Total real area of all inside cells
+    pub fn total(&self) -> T {
+        self.prefix[self.weights.len()][self.compactor.width()]
+    }
+
+    /// Sum of weights over a rectangle of compacted cells
+    pub fn sum_cells(&self, cells: Rect<usize>) -> T {
+        let (x0, y0) = (cells.min.x, cells.min.y);
+        let (x1, y1) = (cells.max.x + 1, cells.max.y + 1);
+
+        self.prefix[y1][x1] + self.prefix[y0][x0] - self.prefix[y0][x1] - self.prefix[y1][x0]
+    }
+
+    /// Number of original points in `rect` that are inside the shape.
+    ///
+    /// The edges of `rect` must line up with compacted cell boundaries, which is always the case
+    /// for corners built from key points.
+    pub fn count_inside(&self, rect: Rect<T>) -> T {
+        let min = self.compactor.compact(rect.min);
+        let max = self.compactor.compact(rect.max);
+
+        let aligned = *self.compactor.xs().span(min.x).start() == rect.min.x
+            && *self.compactor.ys().span(min.y).start() == rect.min.y
+            && *self.compactor.xs().span(max.x).end() == rect.max.x
+            && *self.compactor.ys().span(max.y).end() == rect.max.y;
+        assert!(aligned, "rectangle does not line up with compacted cells");
+
+        self.sum_cells(Rect { min, max })
+    }
+
+    pub fn is_fully_inside(&self, rect: Rect<T>) -> bool {
+        self.count_inside(rect) == rect.area_inclusive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn l_shape() {
+        // a 2-wide column along the left and a 2-high row along the bottom of a 10x10 square
+        let keys = [Point { x: 0_u64, y: 0 }, Point { x: 1, y: 7 }, Point { x: 9, y: 8 }, Point { x: 9, y: 9 }];
+        let compactor: Compactor<u64> = keys.into_iter().collect();
+
+        let l_shape = |pt: Point<u64>| pt.x <= 1 || pt.y >= 8;
+        let grid = CompressedGrid::new(compactor.clone(), |cell| l_shape(compactor.decompact(cell)));
+
+        assert_eq!(grid.total(), 36);
+        assert_eq!(grid.count_inside(Rect::from_corners(Point { x: 0, y: 0 }, Point { x: 9, y: 9 })), 36);
+        assert!(grid.is_fully_inside(Rect::from_corners(Point { x: 0, y: 0 }, Point { x: 1, y: 9 })));
+        assert!(!grid.is_fully_inside(Rect::from_corners(Point { x: 0, y: 0 }, Point { x: 9, y: 7 })));
+        assert_eq!(grid.count_inside(Rect::from_corners(Point { x: 1, y: 7 }, Point { x: 9, y: 9 })), 3 + 16);
+    }
+
+    #[test]
+    #[should_panic(expected = "line up")]
+    fn misaligned() {
+        let compactor: Compactor<u64> = [Point { x: 0, y: 0 }, Point { x: 9, y: 9 }].into_iter().collect();
+        let grid = CompressedGrid::new(compactor, |_| true);
+        grid.count_inside(Rect::from_corners(Point { x: 0, y: 0 }, Point { x: 5, y: 9 }));
+    }
+}
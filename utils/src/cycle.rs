@@ -0,0 +1,172 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// The shape of an eventually periodic sequence x_0, x_1, ...: x_i == x_(i + len) for every
+/// i >= start, and start and len are the smallest such values
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub len: usize,
+}
+impl Cycle {
+    /// The smallest index whose state equals x_n
+    pub fn reduce(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.len
+        }
+    }
+}
+
+/// Floyd's tortoise and hare. Uses constant memory; the sequence must eventually repeat.
+pub fn floyd<S, F>(initial: S, mut step: F) -> Cycle where S: Clone + Eq, F: FnMut(&S) -> S {
+    let mut tortoise = step(&initial);
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        let half = step(&hare);
+        hare = step(&half);
+    }
+
+    let mut start = 0;
+    tortoise = initial;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    let mut len = 1;
+    hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        len += 1;
+    }
+
+    Cycle { start, len }
+}
+
+/// Brent's algorithm. Uses constant memory and fewer steps than [`floyd`]; the sequence must
+/// eventually repeat.
+pub fn brent<S, F>(initial: S, mut step: F) -> Cycle where S: Clone + Eq, F: FnMut(&S) -> S {
+    let (mut power, mut len) = (1, 1);
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial);
+    while tortoise != hare {
+        if power == len {
+            tortoise = hare.clone();
+            power *= 2;
+            len = 0;
+        }
+        hare = step(&hare);
+        len += 1;
+    }
+
+    // walk a hare `len` steps ahead of the tortoise until they meet at the cycle start
+    tortoise = initial.clone();
+    hare = initial;
+    for _ in 0..len {
+        hare = step(&hare);
+    }
+
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    Cycle { start, len }
+}
+
+/// Every state seen so far, with the step it was first seen at
+#[derive(Debug, Clone)]
+pub struct StateHistory<S> {
+    states: Vec<S>,
+    seen: HashMap<S, usize>,
+}
+impl<S> StateHistory<S> where S: Clone + Hash + Eq {
+    pub fn new(initial: S) -> Self {
+        Self { states: vec![initial.clone()], seen: HashMap::from([(initial, 0)]) }
+    }
+
+    /// Record the next state; returns the cycle if this state was seen before
+    pub fn push(&mut self, state: S) -> Option<Cycle> {
+        let step = self.states.len();
+        if let Some(&start) = self.seen.get(&state) {
+            return Some(Cycle { start, len: step - start });
+        }
+
+        self.seen.insert(state.clone(), step);
+        self.states.push(state);
+        None
+    }
+
+    /// The most recently recorded state
+    pub fn last(&self) -> &S {
+        self.states.last().unwrap()
+    }
+
+    /// States by step; once a cycle is found this holds exactly one full period after the prefix
+    pub fn states(&self) -> &[S] {
+        &self.states
+    }
+
+    /// Step until a state repeats
+    pub fn find_cycle<F>(initial: S, mut step: F) -> (Cycle, Self) where F: FnMut(&S) -> S {
+        let mut history = Self::new(initial);
+        loop {
+            let next = step(history.last());
+            if let Some(cycle) = history.push(next) {
+                return (cycle, history);
+            }
+        }
+    }
+}
+
+/// The state after `n` steps, skipping whole periods once the sequence starts repeating
+pub fn fast_forward<S, F>(initial: S, n: usize, mut step: F) -> S where S: Clone + Hash + Eq, F: FnMut(&S) -> S {
+    let mut history = StateHistory::new(initial);
+    while history.states().len() <= n {
+        let next = step(history.last());
+        if let Some(cycle) = history.push(next) {
+            return history.states()[cycle.reduce(n)].clone();
+        }
+    }
+    history.states()[n].clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn next(x: &u64) -> u64 {
+        (x * x + 1) % 255
+    }
+
+    #[test]
+    fn detectors_agree() {
+        for initial in 0..255 {
+            let (cycle, history) = StateHistory::find_cycle(initial, next);
+            assert_eq!(floyd(initial, next), cycle, "floyd from {}", initial);
+            assert_eq!(brent(initial, next), cycle, "brent from {}", initial);
+            assert_eq!(history.states().len(), cycle.start + cycle.len);
+        }
+
+        // 3 -> 10 -> 101 -> 2 -> 5 -> 26 -> 167 -> 95 -> 101
+        assert_eq!(floyd(3, next), Cycle { start: 2, len: 6 });
+    }
+
+    #[test]
+    fn fast_forwarding() {
+        let mut naive = vec![7];
+        for _ in 0..1000 {
+            naive.push(next(naive.last().unwrap()));
+        }
+
+        for n in [0, 1, 5, 17, 999, 1000] {
+            assert_eq!(fast_forward(7, n, next), naive[n], "step {}", n);
+        }
+        assert_eq!(fast_forward(3, 1_000_000_000, next), fast_forward(3, Cycle { start: 2, len: 6 }.reduce(1_000_000_000), next));
+    }
+}
@@ -0,0 +1,257 @@
+use num_traits::PrimInt;
+
+use crate::Point;
+
+/// Where a point lies relative to a closed polygon
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Containment {
+    Inside,
+    Boundary,
+    Outside,
+}
+impl Containment {
+    /// true for both the interior and the boundary
+    pub fn is_covered(self) -> bool {
+        self != Containment::Outside
+    }
+}
+
+fn widen<T: PrimInt>(pt: Point<T>) -> (i128, i128) {
+    (pt.x.to_i128().expect("coordinate out of range"), pt.y.to_i128().expect("coordinate out of range"))
+}
+
+/// Sign of the cross product (b - a) x (c - a)
+fn orientation(a: (i128, i128), b: (i128, i128), c: (i128, i128)) -> i128 {
+    ((b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)).signum()
+}
+
+/// Whether `p`, known to be colinear with a-b, lies on the segment a-b
+fn within(p: (i128, i128), a: (i128, i128), b: (i128, i128)) -> bool {
+    a.0.min(b.0) <= p.0 && p.0 <= a.0.max(b.0) && a.1.min(b.1) <= p.1 && p.1 <= a.1.max(b.1)
+}
+
+/// Whether the closed segments a0-a1 and b0-b1 share at least one point
+pub fn segments_intersect<T: PrimInt>(a0: Point<T>, a1: Point<T>, b0: Point<T>, b1: Point<T>) -> bool {
+    let (a0, a1, b0, b1) = (widen(a0), widen(a1), widen(b0), widen(b1));
+
+    let o1 = orientation(a0, a1, b0);
+    let o2 = orientation(a0, a1, b1);
+    let o3 = orientation(b0, b1, a0);
+    let o4 = orientation(b0, b1, a1);
+
+    if o1 != o2 && o3 != o4 {
+        return true;
+    }
+
+    (o1 == 0 && within(b0, a0, a1))
+        || (o2 == 0 && within(b1, a0, a1))
+        || (o3 == 0 && within(a0, b0, b1))
+        || (o4 == 0 && within(a1, b0, b1))
+}
+
+/// A simple polygon whose edges are all horizontal or vertical.
+///
+/// Vertices are lattice points; the polygon is closed (the last vertex connects to the first).
+#[derive(Debug, Clone)]
+pub struct OrthogonalPolygon<T> {
+    vertices: Vec<Point<T>>,
+    /// vertices with both coordinates doubled, so that midpoints stay integral
+    doubled: Vec<(i128, i128)>,
+}
+impl<T> OrthogonalPolygon<T> where T: PrimInt {
+    pub fn new(vertices: Vec<Point<T>>) -> Self {
+        assert!(vertices.len() >= 4, "polygon needs at least 4 vertices");
+
+        let doubled: Vec<(i128, i128)> = vertices.iter().map(|&v| {
+            let (x, y) = widen(v);
+            (2 * x, 2 * y)
+        }).collect();
+
+        for i in 0..doubled.len() {
+            let a = doubled[i];
+            let b = doubled[(i + 1) % doubled.len()];
+            assert!(a.0 == b.0 || a.1 == b.1, "Invalid pair of vertices: must be orthogonally colinear");
+        }
+
+        Self { vertices, doubled }
+    }
+
+    pub fn vertices(&self) -> &[Point<T>] {
+        &self.vertices
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = (Point<T>, Point<T>)> + '_ {
+        let n = self.vertices.len();
+        (0..n).map(move |i| (self.vertices[i], self.vertices[(i + 1) % n]))
+    }
+
+    fn doubled_edges(&self) -> impl Iterator<Item = ((i128, i128), (i128, i128))> + '_ {
+        let n = self.doubled.len();
+        (0..n).map(move |i| (self.doubled[i], self.doubled[(i + 1) % n]))
+    }
+
+    /// Twice the enclosed area, by the shoelace formula
+    pub fn twice_area(&self) -> i128 {
+        let sum: i128 = self.doubled_edges()
+            .map(|(a, b)| a.0 * b.1 - b.0 * a.1)
+            .sum();
+        // undo the doubling of both coordinates
+        sum.abs() / 4
+    }
+
+    /// Number of lattice points on the boundary
+    pub fn boundary_points(&self) -> i128 {
+        self.doubled_edges()
+            .map(|(a, b)| ((a.0 - b.0).abs() + (a.1 - b.1).abs()) / 2)
+            .sum()
+    }
+
+    /// Number of lattice points strictly inside, by Pick's theorem
+    pub fn interior_points(&self) -> i128 {
+        (self.twice_area() - self.boundary_points() + 2) / 2
+    }
+
+    /// Number of lattice points inside or on the boundary
+    pub fn covered_points(&self) -> i128 {
+        self.interior_points() + self.boundary_points()
+    }
+
+    fn containment_doubled(&self, p: (i128, i128)) -> Containment {
+        let mut crossings = 0;
+
+        for (a, b) in self.doubled_edges() {
+            if within(p, a, b) {
+                return Containment::Boundary;
+            }
+
+            // cast a ray towards +x; count vertical edges with a half-open y range
+            if a.0 == b.0 && a.0 > p.0 && a.1.min(b.1) <= p.1 && p.1 < a.1.max(b.1) {
+                crossings += 1;
+            }
+        }
+
+        if crossings % 2 == 1 {
+            Containment::Inside
+        } else {
+            Containment::Outside
+        }
+    }
+
+    pub fn contains_point(&self, pt: Point<T>) -> Containment {
+        let (x, y) = widen(pt);
+        self.containment_doubled((2 * x, 2 * y))
+    }
+
+    /// Whether every point of the doubled-coordinate segment from `a` to `b` (which must be
+    /// axis-aligned) is covered. Coverage can only change at vertex coordinates, so checking
+    /// those and the midpoints between them is exact.
+    fn covers_segment_doubled(&self, a: (i128, i128), b: (i128, i128)) -> bool {
+        let horizontal = a.1 == b.1;
+        let (lo, hi) = if horizontal { (a.0.min(b.0), a.0.max(b.0)) } else { (a.1.min(b.1), a.1.max(b.1)) };
+        let at = |t: i128| if horizontal { (t, a.1) } else { (a.0, t) };
+
+        let mut breaks: Vec<i128> = self.doubled.iter()
+            .map(|v| if horizontal { v.0 } else { v.1 })
+            .filter(|&t| lo < t && t < hi)
+            .collect();
+        breaks.push(lo);
+        breaks.push(hi);
+        breaks.sort_unstable();
+        breaks.dedup();
+
+        breaks.iter().all(|&t| self.containment_doubled(at(t)).is_covered())
+            && breaks.windows(2).all(|w| self.containment_doubled(at((w[0] + w[1]) / 2)).is_covered())
+    }
+
+    /// Whether the closed axis-aligned rectangle with corners `a` and `b` lies entirely
+    /// inside the closed polygon
+    pub fn contains_rect(&self, a: Point<T>, b: Point<T>) -> bool {
+        let (ax, ay) = widen(a);
+        let (bx, by) = widen(b);
+        let (x0, x1) = (2 * ax.min(bx), 2 * ax.max(bx));
+        let (y0, y1) = (2 * ay.min(by), 2 * ay.max(by));
+
+        if x0 == x1 || y0 == y1 {
+            return self.covers_segment_doubled((x0, y0), (x1, y1));
+        }
+
+        // an edge passing through the open interior always has the outside on one side
+        let cuts_interior = self.doubled_edges().any(|(a, b)| {
+            if a.0 == b.0 {
+                x0 < a.0 && a.0 < x1 && a.1.min(b.1).max(y0) < a.1.max(b.1).min(y1)
+            } else {
+                y0 < a.1 && a.1 < y1 && a.0.min(b.0).max(x0) < a.0.max(b.0).min(x1)
+            }
+        });
+        if cuts_interior {
+            return false;
+        }
+
+        // otherwise the whole interior is on one side of the boundary
+        self.containment_doubled(((x0 + x1) / 2, (y0 + y1) / 2)) == Containment::Inside
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pt(x: i64, y: i64) -> Point<i64> {
+        Point { x, y }
+    }
+
+    /// a 6x6 square with a 2-wide notch cut into its top edge
+    fn notched() -> OrthogonalPolygon<i64> {
+        OrthogonalPolygon::new(vec![
+            pt(0, 0), pt(2, 0), pt(2, 3), pt(4, 3), pt(4, 0), pt(6, 0), pt(6, 6), pt(0, 6),
+        ])
+    }
+
+    #[test]
+    fn area_and_pick() {
+        let square = OrthogonalPolygon::new(vec![pt(0, 0), pt(3, 0), pt(3, 3), pt(0, 3)]);
+        assert_eq!(square.twice_area(), 18);
+        assert_eq!(square.boundary_points(), 12);
+        assert_eq!(square.interior_points(), 4);
+        assert_eq!(square.covered_points(), 16);
+
+        let notched = notched();
+        assert_eq!(notched.twice_area(), 2 * (36 - 6));
+        assert_eq!(notched.covered_points(), 49 - 3);
+    }
+
+    #[test]
+    fn point_in_polygon() {
+        let p = notched();
+        assert_eq!(p.contains_point(pt(1, 1)), Containment::Inside);
+        assert_eq!(p.contains_point(pt(3, 1)), Containment::Outside);
+        assert_eq!(p.contains_point(pt(3, 3)), Containment::Boundary);
+        assert_eq!(p.contains_point(pt(3, 4)), Containment::Inside);
+        assert_eq!(p.contains_point(pt(7, 3)), Containment::Outside);
+        assert_eq!(p.contains_point(pt(-1, 3)), Containment::Outside);
+    }
+
+    #[test]
+    fn segments() {
+        assert!(segments_intersect(pt(0, 0), pt(4, 4), pt(0, 4), pt(4, 0)));
+        assert!(segments_intersect(pt(0, 0), pt(4, 0), pt(4, 0), pt(4, 4)));
+        assert!(segments_intersect(pt(0, 0), pt(4, 0), pt(2, 0), pt(6, 0)));
+        assert!(!segments_intersect(pt(0, 0), pt(4, 0), pt(5, 0), pt(6, 0)));
+        assert!(!segments_intersect(pt(0, 0), pt(4, 0), pt(0, 1), pt(4, 1)));
+    }
+
+    #[test]
+    fn rect_containment() {
+        let p = notched();
+        assert!(p.contains_rect(pt(0, 0), pt(2, 6)));
+        assert!(p.contains_rect(pt(0, 3), pt(6, 6)));
+        assert!(!p.contains_rect(pt(0, 0), pt(6, 6)));
+        assert!(!p.contains_rect(pt(1, 2), pt(5, 4)));
+
+        // degenerate rectangles run along or across the notch
+        assert!(p.contains_rect(pt(0, 3), pt(6, 3)));
+        assert!(!p.contains_rect(pt(0, 2), pt(6, 2)));
+        assert!(p.contains_rect(pt(2, 0), pt(2, 6)));
+        assert!(p.contains_rect(pt(5, 5), pt(5, 5)));
+    }
+}
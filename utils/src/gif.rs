@@ -0,0 +1,387 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    time::Duration,
+};
+
+use crate::{Color, Image};
+
+const MAX_COLORS: usize = 256;
+const MAX_CODE: u16 = 4096;
+
+fn pack(c: Color) -> u32 {
+    (c.r as u32) << 16 | (c.g as u32) << 8 | c.b as u32
+}
+
+/// An animated GIF built frame by frame; every frame must have the size of the first one
+#[derive(Clone, Default)]
+pub struct GifEncoder {
+    frames: Vec<(Image, Duration)>,
+    /// None plays once, Some(0) loops forever
+    repeat: Option<u16>,
+}
+impl GifEncoder {
+    /// Loops forever by default
+    pub fn new() -> Self {
+        Self { frames: vec![], repeat: Some(0) }
+    }
+
+    pub fn with_repeat(mut self, repeat: Option<u16>) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// `delay` is truncated to GIF's resolution of 10ms
+    pub fn add_image(&mut self, image: Image, delay: Duration) {
+        if let Some((first, _)) = self.frames.first() {
+            assert_eq!((image.width(), image.height()), (first.width(), first.height()), "all frames must have the same size");
+        }
+        self.frames.push((image, delay));
+    }
+
+    /// Same inputs as [`Image::from_grid`]
+    pub fn add_grid<T, F>(&mut self, grid: &[Vec<T>], scale: usize, color: F, delay: Duration) where F: Fn(&T) -> Color {
+        self.add_image(Image::from_grid(grid, scale, color), delay);
+    }
+
+    /// One palette shared by all frames: exact if there are at most 256 colours, otherwise
+    /// median cut over all pixels
+    fn palette(&self) -> Vec<Color> {
+        let mut counts: HashMap<u32, (Color, usize)> = HashMap::new();
+        for (image, _) in &self.frames {
+            for c in image.pixels() {
+                counts.entry(pack(c)).or_insert((c, 0)).1 += 1;
+            }
+        }
+
+        let mut colors: Vec<(Color, usize)> = counts.into_values().collect();
+        colors.sort_by_key(|(c, _)| pack(*c));
+        if colors.len() <= MAX_COLORS {
+            colors.into_iter().map(|(c, _)| c).collect()
+        } else {
+            median_cut(colors, MAX_COLORS)
+        }
+    }
+
+    pub fn write<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        assert!(!self.frames.is_empty(), "a GIF needs at least one frame");
+        let first = &self.frames[0].0;
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidInput, msg);
+
+        let dimension = |n: usize| u16::try_from(n).map_err(|_| invalid(format!("{} pixels is too large for a GIF", n)));
+        let (width, height) = (dimension(first.width())?, dimension(first.height())?);
+        for (i, (image, _)) in self.frames.iter().enumerate() {
+            if (image.width(), image.height()) != (first.width(), first.height()) {
+                return Err(invalid(format!(
+                    "frame {} is {}x{} but frame 0 is {}x{}", i, image.width(), image.height(), width, height,
+                )));
+            }
+        }
+
+        let palette = self.palette();
+        // the colour table holds 2^bits entries, at least 2
+        let bits = (usize::BITS - (palette.len().max(2) - 1).leading_zeros()) as u8;
+
+        out.write_all(b"GIF89a")?;
+        out.write_all(&width.to_le_bytes())?;
+        out.write_all(&height.to_le_bytes())?;
+        // global colour table, 8 bits per primary, table size
+        out.write_all(&[0x80 | 0x70 | (bits - 1), 0, 0])?;
+        for i in 0..1 << bits {
+            let c = palette.get(i).copied().unwrap_or(Color::BLACK);
+            out.write_all(&[c.r, c.g, c.b])?;
+        }
+
+        if let Some(repeat) = self.repeat {
+            out.write_all(b"\x21\xff\x0bNETSCAPE2.0\x03\x01")?;
+            out.write_all(&repeat.to_le_bytes())?;
+            out.write_all(&[0])?;
+        }
+
+        let mut lookup: HashMap<u32, u8> = palette.iter().enumerate().map(|(i, &c)| (pack(c), i as u8)).collect();
+        let min_code_size = bits.max(2);
+
+        for (image, delay) in &self.frames {
+            let centis = (delay.as_millis() / 10).min(u16::MAX as u128) as u16;
+            out.write_all(&[0x21, 0xf9, 4, 0])?;
+            out.write_all(&centis.to_le_bytes())?;
+            out.write_all(&[0, 0])?;
+
+            out.write_all(&[0x2c, 0, 0, 0, 0])?;
+            out.write_all(&width.to_le_bytes())?;
+            out.write_all(&height.to_le_bytes())?;
+            out.write_all(&[0])?;
+
+            let indices: Vec<u8> = image.pixels().map(|c| {
+                *lookup.entry(pack(c)).or_insert_with(|| nearest(&palette, c))
+            }).collect();
+
+            out.write_all(&[min_code_size])?;
+            for block in lzw_encode(&indices, min_code_size).chunks(255) {
+                out.write_all(&[block.len() as u8])?;
+                out.write_all(block)?;
+            }
+            out.write_all(&[0])?;
+        }
+
+        out.write_all(&[0x3b])
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.write(&mut out)?;
+        out.flush()
+    }
+}
+
+fn nearest(palette: &[Color], c: Color) -> u8 {
+    let dist = |p: &Color| {
+        let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2);
+        d(p.r, c.r) + d(p.g, c.g) + d(p.b, c.b)
+    };
+    (0..palette.len()).min_by_key(|&i| dist(&palette[i])).unwrap() as u8
+}
+
+/// Reduce weighted colours to at most `max` by repeatedly splitting the box with the widest
+/// channel at its weighted median
+fn median_cut(colors: Vec<(Color, usize)>, max: usize) -> Vec<Color> {
+    let channel = |c: &Color, ch: usize| [c.r, c.g, c.b][ch];
+    let widest = |bucket: &[(Color, usize)]| {
+        (0..3).map(|ch| {
+            let values = bucket.iter().map(|(c, _)| channel(c, ch));
+            (values.clone().max().unwrap() - values.min().unwrap(), ch)
+        }).max().unwrap()
+    };
+
+    let mut buckets = vec![colors];
+    while buckets.len() < max {
+        let Some((idx, ch)) = buckets.iter().enumerate()
+            .filter(|(_, b)| b.len() > 1)
+            .map(|(i, b)| (widest(b), i))
+            .max()
+            .map(|((_, ch), i)| (i, ch))
+        else {
+            break;
+        };
+
+        let mut bucket = buckets.swap_remove(idx);
+        bucket.sort_by_key(|(c, _)| channel(c, ch));
+        let total: usize = bucket.iter().map(|(_, n)| n).sum();
+        let mut seen = 0;
+        let split = bucket.iter().position(|(_, n)| {
+            seen += n;
+            2 * seen >= total
+        }).unwrap().min(bucket.len() - 2) + 1;
+
+        let upper = bucket.split_off(split);
+        buckets.push(bucket);
+        buckets.push(upper);
+    }
+
+    buckets.iter().map(|bucket| {
+        let total: usize = bucket.iter().map(|(_, n)| n).sum();
+        let avg = |ch: usize| (bucket.iter().map(|(c, n)| channel(c, ch) as usize * n).sum::<usize>() / total) as u8;
+        Color::rgb(avg(0), avg(1), avg(2))
+    }).collect()
+}
+
+/// Packs codes of varying width, least significant bit first
+struct BitWriter {
+    bytes: Vec<u8>,
+    acc: u32,
+    len: u8,
+}
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u8) {
+        self.acc |= (code as u32) << self.len;
+        self.len += size;
+        while self.len >= 8 {
+            self.bytes.push(self.acc as u8);
+            self.acc >>= 8;
+            self.len -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.len > 0 {
+            self.bytes.push(self.acc as u8);
+        }
+        self.bytes
+    }
+}
+
+/// GIF flavoured LZW: codes start at min_code_size + 1 bits and grow to 12, then the table is
+/// cleared
+fn lzw_encode(indices: &[u8], min_code_size: u8) -> Vec<u8> {
+    let clear = 1_u16 << min_code_size;
+    let end = clear + 1;
+
+    let mut out = BitWriter { bytes: vec![], acc: 0, len: 0 };
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next = end + 1;
+    let mut size = min_code_size + 1;
+    out.write(clear, size);
+
+    let Some((&first, rest)) = indices.split_first() else {
+        out.write(end, size);
+        return out.finish();
+    };
+
+    let mut prefix = first as u16;
+    for &k in rest {
+        if let Some(&code) = table.get(&(prefix, k)) {
+            prefix = code;
+            continue;
+        }
+
+        out.write(prefix, size);
+        if next >= 1 << size && size < 12 {
+            size += 1;
+        }
+
+        if next < MAX_CODE {
+            table.insert((prefix, k), next);
+            next += 1;
+        } else {
+            out.write(clear, size);
+            table.clear();
+            next = end + 1;
+            size = min_code_size + 1;
+        }
+        prefix = k as u16;
+    }
+
+    out.write(prefix, size);
+    if next >= 1 << size && size < 12 {
+        size += 1;
+    }
+    out.write(end, size);
+    out.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Straightforward GIF LZW decoder to check the encoder against
+    fn lzw_decode(bytes: &[u8], min_code_size: u8) -> Vec<u8> {
+        let clear = 1_usize << min_code_size;
+        let end = clear + 1;
+        let reset = || (0..clear).map(|i| vec![i as u8]).chain([vec![], vec![]]).collect::<Vec<Vec<u8>>>();
+
+        let (mut table, mut size, mut prev): (Vec<Vec<u8>>, u8, Option<usize>) = (reset(), min_code_size + 1, None);
+        let (mut acc, mut len, mut pos) = (0_u32, 0_u8, 0);
+        let mut out = vec![];
+
+        loop {
+            while len < size {
+                acc |= (bytes[pos] as u32) << len;
+                pos += 1;
+                len += 8;
+            }
+            let code = (acc & ((1 << size) - 1)) as usize;
+            acc >>= size;
+            len -= size;
+
+            if code == clear {
+                (table, size, prev) = (reset(), min_code_size + 1, None);
+                continue;
+            }
+            if code == end {
+                return out;
+            }
+
+            let entry = match prev {
+                None => table[code].clone(),
+                Some(p) => {
+                    let entry = if code < table.len() { table[code].clone() } else { [table[p].clone(), vec![table[p][0]]].concat() };
+                    if table.len() < MAX_CODE as usize {
+                        table.push([table[p].clone(), vec![entry[0]]].concat());
+                    }
+                    entry
+                }
+            };
+            if table.len() == 1 << size && size < 12 {
+                size += 1;
+            }
+
+            out.extend(&entry);
+            prev = Some(code);
+        }
+    }
+
+    #[test]
+    fn lzw_round_trip() {
+        let inputs: Vec<Vec<u8>> = vec![
+            vec![],
+            vec![1],
+            vec![0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1],
+            (0..20_000).map(|i| ((i * i) % 7 + (i / 300) % 3) as u8).collect(),
+            (0..50_000_u32).map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8).collect(),
+        ];
+
+        for input in inputs {
+            let min_code_size = 8;
+            assert_eq!(lzw_decode(&lzw_encode(&input, min_code_size), min_code_size), input);
+            let small: Vec<u8> = input.iter().map(|v| v % 4).collect();
+            assert_eq!(lzw_decode(&lzw_encode(&small, 2), 2), small);
+        }
+    }
+
+    #[test]
+    fn quantization() {
+        let colors: Vec<(Color, usize)> = (0..1000).map(|i| (Color::rgb((i % 10 * 25) as u8, (i / 10 % 10 * 25) as u8, (i / 100 * 25) as u8), 1)).collect();
+        let palette = median_cut(colors.clone(), 256);
+        assert_eq!(palette.len(), 256);
+
+        // 1000 colours on a grid 25 apart; every one should land within about a step of the palette
+        for (c, _) in colors {
+            let p = palette[nearest(&palette, c) as usize];
+            let err = (p.r as i32 - c.r as i32).abs().max((p.g as i32 - c.g as i32).abs()).max((p.b as i32 - c.b as i32).abs());
+            assert!(err <= 40, "{} off by {}", pack(c), err);
+        }
+    }
+
+    #[test]
+    fn container() {
+        let mut gif = GifEncoder::new();
+        let palette = [Color::BLACK, Color::rgb(255, 0, 0), Color::WHITE];
+        gif.add_grid(&[vec![0, 1], vec![2, 0]], 3, |&v: &usize| palette[v], Duration::from_millis(250));
+        gif.add_grid(&[vec![1, 1], vec![2, 2]], 3, |&v: &usize| palette[v], Duration::from_millis(500));
+
+        let mut bytes = vec![];
+        gif.write(&mut bytes).unwrap();
+        assert_eq!(&bytes[..6], b"GIF89a");
+        assert_eq!(&bytes[6..10], &[6, 0, 6, 0]);
+        // 4 entry global colour table
+        assert_eq!(bytes[10], 0xf1);
+        assert_eq!(&bytes[13..25], &[0, 0, 0, 255, 0, 0, 255, 255, 255, 0, 0, 0]);
+        assert_eq!(&bytes[25..28], b"\x21\xff\x0b");
+        // the first graphic control block carries 25 centiseconds
+        assert_eq!(&bytes[44..50], &[0x21, 0xf9, 4, 0, 25, 0]);
+        assert_eq!(*bytes.last().unwrap(), 0x3b);
+    }
+
+    #[test]
+    fn invalid_sizes() {
+        let mut wide = GifEncoder::new();
+        wide.add_image(Image::new(65536, 1, Color::BLACK), Duration::ZERO);
+        assert_eq!(wide.write(&mut vec![]).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+
+        let mut mixed = GifEncoder::new();
+        mixed.add_image(Image::new(2, 2, Color::BLACK), Duration::ZERO);
+        mixed.frames.push((Image::new(3, 2, Color::BLACK), Duration::ZERO));
+        let err = mixed.write(&mut vec![]).unwrap_err();
+        assert_eq!(err.to_string(), "frame 1 is 3x2 but frame 0 is 2x2");
+    }
+}
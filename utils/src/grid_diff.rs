@@ -0,0 +1,165 @@
+use std::fmt::{self, Display};
+
+use crate::{Attrs, Color, Overlay, Point, Precedence, Style, StyleUtil, StyledChar};
+
+/// How many coordinates a summary lists before eliding the rest
+const SUMMARY_LIMIT: usize = 10;
+
+/// Background for cells that differ between two grids
+pub const DIFF_STYLE: Style = Style { fg: None, bg: Some(Color::rgb(150, 30, 30)), attrs: Attrs::NONE };
+
+/// Widest row and number of rows
+fn size<T>(grid: &[Vec<T>]) -> (usize, usize) {
+    (grid.iter().map(Vec::len).max().unwrap_or(0), grid.len())
+}
+
+/// The cells where two grids disagree. A cell missing from one grid (ragged rows or different
+/// sizes) counts as different.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GridDiff {
+    left_size: (usize, usize),
+    right_size: (usize, usize),
+    positions: Vec<Point<usize>>,
+}
+impl GridDiff {
+    pub fn new<T: PartialEq>(left: &[Vec<T>], right: &[Vec<T>]) -> Self {
+        Self::new_by(left, right, |a, b| a == b)
+    }
+
+    /// Diff with a custom notion of equal cells, e.g. comparing only the characters of styled grids
+    pub fn new_by<A, B, F>(left: &[Vec<A>], right: &[Vec<B>], eq: F) -> Self where F: Fn(&A, &B) -> bool {
+        let mut positions = vec![];
+
+        for y in 0..left.len().max(right.len()) {
+            let (l, r) = (left.get(y), right.get(y));
+            let width = l.map_or(0, Vec::len).max(r.map_or(0, Vec::len));
+            for x in 0..width {
+                let same = match (l.and_then(|row| row.get(x)), r.and_then(|row| row.get(x))) {
+                    (Some(a), Some(b)) => eq(a, b),
+                    _ => false,
+                };
+                if !same {
+                    positions.push(Point { x, y });
+                }
+            }
+        }
+
+        Self { left_size: size(left), right_size: size(right), positions }
+    }
+
+    /// Differing cells in reading order
+    pub fn positions(&self) -> &[Point<usize>] {
+        &self.positions
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// `style` on every differing cell
+    pub fn overlay(&self, style: Style) -> Overlay {
+        Overlay::uniform(self.positions.iter().copied(), style)
+    }
+}
+impl Display for GridDiff {
+    /// e.g. `2 cells differ: 1,0; 3,2`, listing at most the first ten
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.left_size != self.right_size {
+            let (lw, lh) = self.left_size;
+            let (rw, rh) = self.right_size;
+            write!(f, "sizes differ ({}x{} vs {}x{}), ", lw, lh, rw, rh)?;
+        }
+
+        match self.len() {
+            0 => return write!(f, "no cells differ"),
+            1 => write!(f, "1 cell differs: ")?,
+            n => write!(f, "{} cells differ: ", n)?,
+        }
+
+        let shown: Vec<String> = self.positions.iter().take(SUMMARY_LIMIT).map(Point::to_string).collect();
+        write!(f, "{}", shown.join("; "))?;
+        if self.len() > SUMMARY_LIMIT {
+            write!(f, "; and {} more", self.len() - SUMMARY_LIMIT)?;
+        }
+        Ok(())
+    }
+}
+
+/// `left` and `right` next to each other, `gap` spaces apart. Short rows and the shorter grid
+/// are padded with spaces so the right grid stays aligned.
+pub fn side_by_side(left: &[Vec<StyledChar>], right: &[Vec<StyledChar>], gap: usize) -> Vec<Vec<StyledChar>> {
+    let (left_width, _) = size(left);
+    let blank = StyledChar::of(' ');
+
+    (0..left.len().max(right.len())).map(|y| {
+        let mut row = left.get(y).cloned().unwrap_or_default();
+        row.resize(left_width + gap, blank);
+        row.extend(right.get(y).into_iter().flatten().copied());
+        row
+    }).collect()
+}
+
+/// `right` with the cells whose character differs from `left` highlighted by [`DIFF_STYLE`]
+pub fn diff_overlay(left: &[Vec<StyledChar>], right: &[Vec<StyledChar>]) -> Vec<Vec<StyledChar>> {
+    let diff = GridDiff::new_by(left, right, |a, b| a.chr == b.chr);
+    right.to_vec().flatten(&[diff.overlay(DIFF_STYLE)], Precedence::Overlay)
+}
+
+/// [`side_by_side`] with differing characters highlighted on both sides, and the diff itself
+pub fn compare_grids(left: &[Vec<StyledChar>], right: &[Vec<StyledChar>], gap: usize) -> (Vec<Vec<StyledChar>>, GridDiff) {
+    let diff = GridDiff::new_by(left, right, |a, b| a.chr == b.chr);
+    let layers = [diff.overlay(DIFF_STYLE)];
+    let joined = side_by_side(
+        &left.to_vec().flatten(&layers, Precedence::Overlay),
+        &right.to_vec().flatten(&layers, Precedence::Overlay),
+        gap,
+    );
+    (joined, diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::to_plain_string;
+
+    fn styled(lines: &[&str]) -> Vec<Vec<StyledChar>> {
+        lines.iter().map(|l| l.chars().map(StyledChar::of).collect()).collect()
+    }
+
+    #[test]
+    fn differences() {
+        let same = GridDiff::new(&[vec![1, 2]], &[vec![1, 2]]);
+        assert!(same.is_empty());
+        assert_eq!(same.to_string(), "no cells differ");
+
+        let diff = GridDiff::new(&[vec![1, 2, 3], vec![4, 5, 6]], &[vec![1, 0, 3], vec![4, 5]]);
+        assert_eq!(diff.positions(), &[Point { x: 1, y: 0 }, Point { x: 2, y: 1 }]);
+        assert_eq!(diff.to_string(), "2 cells differ: 1,0; 2,1");
+
+        let many = GridDiff::new(&[vec![0; 12]], &[vec![1; 13]]);
+        assert_eq!(many.len(), 13);
+        assert_eq!(many.to_string(), "sizes differ (12x1 vs 13x1), 13 cells differ: 0,0; 1,0; 2,0; 3,0; 4,0; 5,0; 6,0; 7,0; 8,0; 9,0; and 3 more");
+    }
+
+    #[test]
+    fn rendering() {
+        let left = styled(&["ab", "c"]);
+        let right = styled(&["ax", "c", "de"]);
+
+        assert_eq!(to_plain_string(&side_by_side(&left, &right, 2)), "ab  ax\nc   c\n    de\n");
+
+        let overlaid = diff_overlay(&left, &right);
+        assert!(overlaid[0][0].style == Style::default());
+        assert!(overlaid[0][1].style == DIFF_STYLE);
+        assert!(overlaid[2][1].style == DIFF_STYLE);
+
+        let (joined, diff) = compare_grids(&left, &right, 1);
+        assert_eq!(diff.len(), 3);
+        assert!(joined[0][1].style == DIFF_STYLE && joined[0][4].style == DIFF_STYLE);
+        assert!(joined[1][0].style == Style::default());
+    }
+}
@@ -0,0 +1,178 @@
+use std::{fmt::Write as _, fs, io, path::Path};
+
+use crate::{svg::escape, Attrs, Color, Style, StyledChar};
+
+/// Page colours, used wherever a cell has no colour of its own
+const PAGE_FG: Color = Color::rgb(0xdd, 0xdd, 0xdd);
+const PAGE_BG: Color = Color::rgb(0x11, 0x11, 0x11);
+
+const SCRIPT: &str = r#"
+const frames = document.querySelectorAll("pre.frame");
+const caption = document.getElementById("caption");
+const slider = document.getElementById("slider");
+const tip = document.getElementById("tip");
+function show(i) {
+  frames.forEach((f, j) => f.hidden = j !== i);
+  caption.textContent = frames[i].dataset.caption;
+}
+if (slider) slider.oninput = () => show(+slider.value);
+if (frames.length) show(0);
+frames.forEach(f => {
+  f.onmousemove = e => {
+    const r = f.getBoundingClientRect();
+    const x = Math.floor((e.clientX - r.left) / r.width * f.dataset.cols);
+    const y = Math.floor((e.clientY - r.top) / r.height * f.dataset.rows);
+    tip.textContent = x + "," + y;
+    tip.style.left = e.pageX + 12 + "px";
+    tip.style.top = e.pageY + 12 + "px";
+    tip.hidden = false;
+  };
+  f.onmouseleave = () => tip.hidden = true;
+});
+"#;
+
+/// Inline CSS for a style, empty for the default one
+fn declarations(style: Style) -> String {
+    let (mut fg, mut bg) = (style.fg, style.bg);
+    if style.attrs.contains(Attrs::REVERSE) {
+        (fg, bg) = (Some(bg.unwrap_or(PAGE_BG)), Some(fg.unwrap_or(PAGE_FG)));
+    }
+
+    let mut out = String::new();
+    if let Some(c) = fg {
+        write!(out, "color:{};", c.to_hex()).unwrap();
+    }
+    if let Some(c) = bg {
+        write!(out, "background:{};", c.to_hex()).unwrap();
+    }
+    if style.attrs.contains(Attrs::BOLD) {
+        out.push_str("font-weight:bold;");
+    }
+    if style.attrs.contains(Attrs::DIM) {
+        out.push_str("opacity:0.6;");
+    }
+    if style.attrs.contains(Attrs::UNDERLINE) {
+        out.push_str("text-decoration:underline;");
+    }
+    out
+}
+
+/// `grid` as HTML text, one `<span>` per run of equally styled cells
+pub fn grid_to_html(grid: &[Vec<StyledChar>]) -> String {
+    let mut out = String::new();
+
+    for row in grid {
+        for run in row.chunk_by(|a, b| a.style == b.style) {
+            let text = escape(&run.iter().map(|c| c.chr).collect::<String>());
+            match declarations(run[0].style) {
+                css if css.is_empty() => out.push_str(&text),
+                css => write!(out, "<span style=\"{}\">{}</span>", css, text).unwrap(),
+            }
+        }
+        out.push('\n');
+    }
+
+    out
+}
+
+/// A self-contained HTML page of one or more styled grids.
+///
+/// Several frames get a slider to step through them; hovering a cell shows its `x,y`.
+#[derive(Clone)]
+pub struct HtmlReport {
+    title: String,
+    /// caption, columns, rows and rendered body of each frame
+    frames: Vec<(String, usize, usize, String)>,
+}
+impl HtmlReport {
+    pub fn new(title: &str) -> Self {
+        Self { title: title.to_owned(), frames: vec![] }
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn frame(&mut self, grid: &[Vec<StyledChar>], caption: &str) -> &mut Self {
+        let cols = grid.iter().map(Vec::len).max().unwrap_or(0);
+        self.frames.push((caption.to_owned(), cols, grid.len(), grid_to_html(grid)));
+        self
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let title = escape(&self.title);
+
+        writeln!(out, "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>", title).unwrap();
+        writeln!(
+            out,
+            "<style>\nbody {{ background:{}; color:{}; font-family:monospace; }}\n\
+             pre.frame {{ display:inline-block; margin:0; line-height:1.2; cursor:crosshair; }}\n\
+             #tip {{ position:absolute; padding:2px 4px; background:#000; border:1px solid #888; pointer-events:none; }}\n</style>",
+            PAGE_BG.to_hex(), PAGE_FG.to_hex(),
+        ).unwrap();
+        writeln!(out, "</head>\n<body>\n<h1>{}</h1>", title).unwrap();
+
+        if self.frames.len() > 1 {
+            writeln!(out, "<input id=\"slider\" type=\"range\" min=\"0\" max=\"{}\" value=\"0\">", self.frames.len() - 1).unwrap();
+        }
+        writeln!(out, "<p id=\"caption\"></p>").unwrap();
+
+        for (caption, cols, rows, body) in &self.frames {
+            write!(out, "<pre class=\"frame\" data-caption=\"{}\" data-cols=\"{}\" data-rows=\"{}\">", escape(caption), cols, rows).unwrap();
+            out.push_str(body);
+            out.push_str("</pre>\n");
+        }
+
+        writeln!(out, "<div id=\"tip\" hidden></div>\n<script>{}</script>\n</body>\n</html>", SCRIPT).unwrap();
+        out
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.render())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs() {
+        let red = Style::fg(Some(Color::rgb(255, 0, 0)));
+        let mut grid: Vec<Vec<StyledChar>> = ["#<.", "..&"].iter().map(|l| l.chars().map(StyledChar::of).collect()).collect();
+        grid[0][0].style = red;
+        grid[0][1].style = red;
+        grid[1][2].style = Style::bg(Some(Color::rgb(0, 0, 255))).with_attrs(Attrs::BOLD | Attrs::REVERSE);
+
+        assert_eq!(
+            grid_to_html(&grid),
+            "<span style=\"color:#ff0000;\">#&lt;</span>.\n..<span style=\"color:#0000ff;background:#dddddd;font-weight:bold;\">&amp;</span>\n",
+        );
+    }
+
+    #[test]
+    fn report() {
+        let grid: Vec<Vec<StyledChar>> = vec!["ab".chars().map(StyledChar::of).collect()];
+
+        let mut single = HtmlReport::new("one");
+        single.frame(&grid, "only");
+        let page = single.render();
+        assert!(page.starts_with("<!DOCTYPE html>"));
+        assert!(page.contains("<pre class=\"frame\" data-caption=\"only\" data-cols=\"2\" data-rows=\"1\">ab\n</pre>"));
+        assert!(!page.contains("id=\"slider\""));
+
+        let mut animated = HtmlReport::new("a & b");
+        animated.frame(&grid, "first").frame(&grid, "\"second\"");
+        let page = animated.render();
+        assert_eq!(animated.len(), 2);
+        assert!(page.contains("<title>a &amp; b</title>"));
+        assert!(page.contains("<input id=\"slider\" type=\"range\" min=\"0\" max=\"1\" value=\"0\">"));
+        assert!(page.contains("data-caption=\"&quot;second&quot;\""));
+        assert_eq!(page.matches("<pre class=\"frame\"").count(), 2);
+    }
+}
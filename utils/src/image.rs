@@ -0,0 +1,260 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use crate::Color;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// binary colour PPM (P6)
+    Ppm,
+    /// binary greyscale PGM (P5)
+    Pgm,
+    /// truecolour PNG with uncompressed deflate blocks
+    Png,
+}
+impl ImageFormat {
+    /// Guess from a `.ppm`, `.pgm` or `.png` extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "ppm" => Some(ImageFormat::Ppm),
+            "pgm" => Some(ImageFormat::Pgm),
+            "png" => Some(ImageFormat::Png),
+            _ => None,
+        }
+    }
+}
+
+/// An RGB raster, usually made from a grid with one square block of pixels per cell
+#[derive(Clone)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+impl Image {
+    pub fn new(width: usize, height: usize, fill: Color) -> Self {
+        Self { width, height, pixels: vec![fill; width * height] }
+    }
+
+    /// Every cell becomes a `scale` x `scale` block coloured by `color`
+    pub fn from_grid<T, F>(grid: &[Vec<T>], scale: usize, color: F) -> Self where F: Fn(&T) -> Color {
+        assert!(scale > 0, "scale must be positive");
+        let (cols, rows) = (grid.first().map_or(0, Vec::len), grid.len());
+        let mut image = Self::new(cols * scale, rows * scale, Color::BLACK);
+
+        for (y, row) in grid.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                let c = color(cell);
+                for py in y * scale..(y + 1) * scale {
+                    image.pixels[py * image.width + x * scale..py * image.width + (x + 1) * scale].fill(c);
+                }
+            }
+        }
+
+        image
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Color {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, c: Color) {
+        self.pixels[y * self.width + x] = c;
+    }
+
+    /// All pixels, row by row
+    pub fn pixels(&self) -> impl Iterator<Item = Color> + '_ {
+        self.pixels.iter().copied()
+    }
+
+    fn rgb_bytes(&self) -> impl Iterator<Item = u8> + '_ {
+        self.pixels.iter().flat_map(|c| [c.r, c.g, c.b])
+    }
+
+    /// Rec. 601 luma
+    fn grey(c: Color) -> u8 {
+        ((299 * c.r as u32 + 587 * c.g as u32 + 114 * c.b as u32 + 500) / 1000) as u8
+    }
+
+    pub fn write_ppm<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        write!(out, "P6\n{} {}\n255\n", self.width, self.height)?;
+        out.write_all(&self.rgb_bytes().collect::<Vec<u8>>())
+    }
+
+    pub fn write_pgm<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        write!(out, "P5\n{} {}\n255\n", self.width, self.height)?;
+        out.write_all(&self.pixels.iter().map(|&c| Self::grey(c)).collect::<Vec<u8>>())
+    }
+
+    pub fn write_png<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        out.write_all(&PNG_SIGNATURE)?;
+
+        let mut header = vec![];
+        header.extend((self.width as u32).to_be_bytes());
+        header.extend((self.height as u32).to_be_bytes());
+        // 8 bits per channel, truecolour, deflate, no filtering, no interlace
+        header.extend([8, 2, 0, 0, 0]);
+        write_chunk(out, b"IHDR", &header)?;
+
+        // every scanline starts with filter type 0
+        let mut raw = Vec::with_capacity(self.height * (1 + 3 * self.width));
+        for row in self.pixels.chunks(self.width.max(1)).take(self.height) {
+            raw.push(0);
+            raw.extend(row.iter().flat_map(|c| [c.r, c.g, c.b]));
+        }
+        write_chunk(out, b"IDAT", &zlib_stored(&raw))?;
+
+        write_chunk(out, b"IEND", &[])
+    }
+
+    pub fn write<W: Write>(&self, out: &mut W, format: ImageFormat) -> io::Result<()> {
+        match format {
+            ImageFormat::Ppm => self.write_ppm(out),
+            ImageFormat::Pgm => self.write_pgm(out),
+            ImageFormat::Png => self.write_png(out),
+        }
+    }
+
+    /// Write to `path` in the format given by its extension
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let format = ImageFormat::from_path(path).ok_or_else(|| io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("unknown image format for {}", path.display()),
+        ))?;
+
+        let mut out = BufWriter::new(File::create(path)?);
+        self.write(&mut out, format)?;
+        out.flush()
+    }
+}
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0_u32;
+    for &b in bytes {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1_u32, 0_u32);
+    for chunk in bytes.chunks(5552) {
+        for &x in chunk {
+            a += x as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+fn write_chunk<W: Write>(out: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    out.write_all(&(data.len() as u32).to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(data)?;
+
+    let mut crc_input = kind.to_vec();
+    crc_input.extend(data);
+    out.write_all(&crc32(&crc_input).to_be_bytes())
+}
+
+/// A zlib stream of uncompressed deflate blocks
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = 0xffff;
+
+    let mut out = vec![0x78, 0x01];
+    let blocks: Vec<&[u8]> = if data.is_empty() { vec![&[]] } else { data.chunks(MAX_BLOCK).collect() };
+    for (i, block) in blocks.iter().enumerate() {
+        out.push((i + 1 == blocks.len()) as u8);
+        out.extend((block.len() as u16).to_le_bytes());
+        out.extend((!(block.len() as u16)).to_le_bytes());
+        out.extend(*block);
+    }
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Image {
+        let grid = vec![vec![0, 1], vec![2, 0]];
+        let palette = [Color::BLACK, Color::rgb(255, 0, 0), Color::WHITE];
+        Image::from_grid(&grid, 2, |&v| palette[v])
+    }
+
+    #[test]
+    fn netpbm() {
+        let image = sample();
+        assert_eq!((image.width(), image.height()), (4, 4));
+        assert!(image.get(3, 1) == Color::rgb(255, 0, 0));
+        assert!(image.get(1, 2) == Color::WHITE);
+
+        let mut ppm = vec![];
+        image.write_ppm(&mut ppm).unwrap();
+        assert!(ppm.starts_with(b"P6\n4 4\n255\n"));
+        assert_eq!(ppm.len(), 11 + 4 * 4 * 3);
+        assert_eq!(&ppm[11 + 6..11 + 9], &[255, 0, 0]);
+
+        let mut pgm = vec![];
+        image.write_pgm(&mut pgm).unwrap();
+        assert_eq!(&pgm[11..15], &[0, 0, 76, 76]);
+        assert_eq!(pgm[11 + 8], 255);
+    }
+
+    #[test]
+    fn png_structure() {
+        let image = sample();
+        let mut png = vec![];
+        image.write_png(&mut png).unwrap();
+        assert_eq!(&png[..8], &PNG_SIGNATURE);
+
+        // walk the chunks, checking every CRC and collecting the image data
+        let mut pos = 8;
+        let mut kinds = vec![];
+        let mut idat = vec![];
+        while pos < png.len() {
+            let len = u32::from_be_bytes(png[pos..pos + 4].try_into().unwrap()) as usize;
+            let body = &png[pos + 4..pos + 8 + len];
+            let crc = u32::from_be_bytes(png[pos + 8 + len..pos + 12 + len].try_into().unwrap());
+            assert_eq!(crc32(body), crc);
+
+            kinds.push(String::from_utf8(body[..4].to_vec()).unwrap());
+            if &body[..4] == b"IDAT" {
+                idat.extend(&body[4..]);
+            }
+            pos += 12 + len;
+        }
+        assert_eq!(kinds, vec!["IHDR", "IDAT", "IEND"]);
+
+        // a single stored block holding 4 scanlines of filter byte + 4 RGB pixels
+        let raw_len = 4 * (1 + 4 * 3);
+        assert_eq!(&idat[..3], &[0x78, 0x01, 1]);
+        assert_eq!(u16::from_le_bytes([idat[3], idat[4]]) as usize, raw_len);
+        let raw = &idat[7..7 + raw_len];
+        assert_eq!(&raw[..7], &[0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(&raw[7..13], &[255, 0, 0, 255, 0, 0]);
+        assert_eq!(u32::from_be_bytes(idat[7 + raw_len..].try_into().unwrap()), adler32(raw));
+
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+}
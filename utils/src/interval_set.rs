@@ -0,0 +1,201 @@
+use std::ops::RangeInclusive;
+
+use num_traits::PrimInt;
+
+/// A set of integers, stored as sorted inclusive ranges that neither overlap nor touch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntervalSet<T> {
+    ranges: Vec<RangeInclusive<T>>,
+}
+impl<T> Default for IntervalSet<T> where T: PrimInt {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<T> IntervalSet<T> where T: PrimInt {
+    pub fn new() -> Self {
+        Self { ranges: vec![] }
+    }
+
+    pub fn ranges(&self) -> &[RangeInclusive<T>] {
+        &self.ranges
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, RangeInclusive<T>> {
+        self.ranges.iter()
+    }
+
+    /// Total number of contained values
+    pub fn len(&self) -> T {
+        self.ranges.iter().fold(T::zero(), |sum, r| sum + (*r.end() - *r.start() + T::one()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn min(&self) -> Option<T> {
+        self.ranges.first().map(|r| *r.start())
+    }
+
+    pub fn max(&self) -> Option<T> {
+        self.ranges.last().map(|r| *r.end())
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let idx = self.ranges.partition_point(|r| *r.end() < value);
+        self.ranges.get(idx).is_some_and(|r| *r.start() <= value)
+    }
+
+    pub fn insert(&mut self, range: RangeInclusive<T>) {
+        let (mut lo, mut hi) = (*range.start(), *range.end());
+        if lo > hi {
+            return;
+        }
+
+        // ranges ending before lo - 1 and starting after hi + 1 are untouched
+        let first = self.ranges.partition_point(|r| *r.end() < lo && *r.end() + T::one() < lo);
+        let last = self.ranges.partition_point(|r| *r.start() <= hi || *r.start() - T::one() <= hi);
+
+        if first < last {
+            lo = lo.min(*self.ranges[first].start());
+            hi = hi.max(*self.ranges[last - 1].end());
+        }
+        self.ranges.splice(first..last, [lo..=hi]);
+    }
+
+    pub fn remove(&mut self, range: RangeInclusive<T>) {
+        let (lo, hi) = (*range.start(), *range.end());
+        if lo > hi {
+            return;
+        }
+
+        let first = self.ranges.partition_point(|r| *r.end() < lo);
+        let last = self.ranges.partition_point(|r| *r.start() <= hi);
+        if first >= last {
+            return;
+        }
+
+        let mut kept = vec![];
+        let (left, right) = (&self.ranges[first], &self.ranges[last - 1]);
+        if *left.start() < lo {
+            kept.push(*left.start()..=(lo - T::one()));
+        }
+        if *right.end() > hi {
+            kept.push((hi + T::one())..=*right.end());
+        }
+        self.ranges.splice(first..last, kept);
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut out = self.clone();
+        other.iter().for_each(|r| out.insert(r.clone()));
+        out
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = vec![];
+        let (mut i, mut j) = (0, 0);
+
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (&self.ranges[i], &other.ranges[j]);
+            let lo = *a.start().max(b.start());
+            let hi = *a.end().min(b.end());
+            if lo <= hi {
+                ranges.push(lo..=hi);
+            }
+
+            if a.end() < b.end() {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        Self { ranges }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut out = self.clone();
+        other.iter().for_each(|r| out.remove(r.clone()));
+        out
+    }
+
+    /// The ranges of missing values between the first and last contained value
+    pub fn gaps(&self) -> impl Iterator<Item = RangeInclusive<T>> + '_ {
+        self.ranges.windows(2).map(|w| (*w[0].end() + T::one())..=(*w[1].start() - T::one()))
+    }
+}
+impl<T> FromIterator<RangeInclusive<T>> for IntervalSet<T> where T: PrimInt {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<T>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        iter.into_iter().for_each(|r| set.insert(r));
+        set
+    }
+}
+impl<'a, T> IntoIterator for &'a IntervalSet<T> {
+    type Item = &'a RangeInclusive<T>;
+    type IntoIter = std::slice::Iter<'a, RangeInclusive<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.ranges.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(ranges: &[(i32, i32)]) -> IntervalSet<i32> {
+        ranges.iter().map(|&(lo, hi)| lo..=hi).collect()
+    }
+
+    #[test]
+    fn normalization() {
+        let s = set(&[(10, 14), (3, 5), (16, 20), (12, 18), (6, 6)]);
+        assert_eq!(s.ranges(), &[3..=6, 10..=20]);
+        assert_eq!(s.len(), 4 + 11);
+        assert_eq!(s.gaps().collect::<Vec<_>>(), vec![7..=9]);
+
+        assert!(s.contains(3));
+        assert!(s.contains(6));
+        assert!(!s.contains(8));
+        assert!(s.contains(20));
+        assert!(!s.contains(21));
+        assert!(!s.contains(i32::MIN));
+    }
+
+    #[test]
+    fn extremes() {
+        let mut s = set(&[(i32::MAX - 1, i32::MAX), (i32::MIN, i32::MIN + 2)]);
+        s.insert(i32::MAX - 3..=i32::MAX - 2);
+        assert_eq!(s.ranges(), &[i32::MIN..=i32::MIN + 2, i32::MAX - 3..=i32::MAX]);
+        s.remove(i32::MIN..=i32::MIN);
+        assert_eq!(s.min(), Some(i32::MIN + 1));
+    }
+
+    #[test]
+    fn removal() {
+        let mut s = set(&[(0, 10), (20, 30)]);
+        s.remove(5..=24);
+        assert_eq!(s.ranges(), &[0..=4, 25..=30]);
+        s.remove(0..=4);
+        assert_eq!(s.ranges(), &[25..=30]);
+        s.remove(40..=50);
+        assert_eq!(s.ranges(), &[25..=30]);
+    }
+
+    #[test]
+    fn algebra() {
+        let a = set(&[(0, 10), (20, 30)]);
+        let b = set(&[(5, 25), (29, 40)]);
+
+        assert_eq!(a.union(&b), set(&[(0, 40)]));
+        assert_eq!(a.intersection(&b), set(&[(5, 10), (20, 25), (29, 30)]));
+        assert_eq!(a.difference(&b), set(&[(0, 4), (26, 28)]));
+        assert_eq!(b.difference(&a), set(&[(11, 19), (31, 40)]));
+
+        let union = a.union(&b);
+        assert_eq!(union.len(), a.len() + b.len() - a.intersection(&b).len());
+    }
+}
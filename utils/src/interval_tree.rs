@@ -0,0 +1,251 @@
+use std::ops::RangeInclusive;
+
+use num_traits::PrimInt;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SweepKind {
+    /// the range covers `pos` and everything after it, until its end
+    Start,
+    /// the range covers `pos` but nothing after it
+    End,
+}
+
+/// A range boundary, as seen by a sweep line moving towards +inf
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SweepEvent<T> {
+    pub pos: T,
+    pub kind: SweepKind,
+    /// index of the range in the list the tree was built from
+    pub id: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Node<T> {
+    center: T,
+    /// ids of ranges containing `center`, by ascending start
+    by_start: Vec<usize>,
+    /// the same ids, by descending end
+    by_end: Vec<usize>,
+    left: Option<usize>,
+    right: Option<usize>,
+}
+
+/// A static centered interval tree over inclusive ranges.
+///
+/// Unlike [`crate::IntervalSet`], every range keeps its identity (its index in the input), so
+/// queries can report which of the original ranges cover a point.
+#[derive(Debug, Clone)]
+pub struct IntervalTree<T> {
+    ranges: Vec<RangeInclusive<T>>,
+    nodes: Vec<Node<T>>,
+    root: Option<usize>,
+    starts: Vec<T>,
+    ends: Vec<T>,
+}
+impl<T> IntervalTree<T> where T: PrimInt {
+    /// Empty ranges are kept (so ids line up) but never reported
+    pub fn new(ranges: Vec<RangeInclusive<T>>) -> Self {
+        let ids: Vec<usize> = (0..ranges.len()).filter(|&id| !ranges[id].is_empty()).collect();
+
+        let mut starts: Vec<T> = ids.iter().map(|&id| *ranges[id].start()).collect();
+        let mut ends: Vec<T> = ids.iter().map(|&id| *ranges[id].end()).collect();
+        starts.sort_unstable();
+        ends.sort_unstable();
+
+        let mut tree = Self { ranges, nodes: vec![], root: None, starts, ends };
+        tree.root = tree.build(ids);
+        tree
+    }
+
+    fn build(&mut self, ids: Vec<usize>) -> Option<usize> {
+        if ids.is_empty() {
+            return None;
+        }
+
+        let mut endpoints: Vec<T> = ids.iter().flat_map(|&id| [*self.ranges[id].start(), *self.ranges[id].end()]).collect();
+        let mid = endpoints.len() / 2;
+        let center = *endpoints.select_nth_unstable(mid).1;
+
+        let (mut here, mut left, mut right) = (vec![], vec![], vec![]);
+        for id in ids {
+            let r = &self.ranges[id];
+            if *r.end() < center {
+                left.push(id);
+            } else if *r.start() > center {
+                right.push(id);
+            } else {
+                here.push(id);
+            }
+        }
+
+        let mut by_start = here.clone();
+        by_start.sort_by_key(|&id| *self.ranges[id].start());
+        let mut by_end = here;
+        by_end.sort_by_key(|&id| std::cmp::Reverse(*self.ranges[id].end()));
+
+        let left = self.build(left);
+        let right = self.build(right);
+        self.nodes.push(Node { center, by_start, by_end, left, right });
+        Some(self.nodes.len() - 1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn range(&self, id: usize) -> &RangeInclusive<T> {
+        &self.ranges[id]
+    }
+
+    /// Ids of all ranges containing `point`, in no particular order
+    pub fn stab(&self, point: T) -> Vec<usize> {
+        let mut out = vec![];
+        let mut cur = self.root;
+
+        while let Some(idx) = cur {
+            let node = &self.nodes[idx];
+            if point < node.center {
+                out.extend(node.by_start.iter().take_while(|&&id| *self.ranges[id].start() <= point));
+                cur = node.left;
+            } else if point > node.center {
+                out.extend(node.by_end.iter().take_while(|&&id| *self.ranges[id].end() >= point));
+                cur = node.right;
+            } else {
+                out.extend(&node.by_start);
+                break;
+            }
+        }
+
+        out
+    }
+
+    /// Ids of all ranges sharing at least one value with `query`, in no particular order
+    pub fn overlapping(&self, query: RangeInclusive<T>) -> Vec<usize> {
+        let mut out = vec![];
+        if query.is_empty() {
+            return out;
+        }
+
+        let (lo, hi) = (*query.start(), *query.end());
+        let mut stack: Vec<usize> = self.root.into_iter().collect();
+        while let Some(idx) = stack.pop() {
+            let node = &self.nodes[idx];
+            out.extend(node.by_start.iter().filter(|&&id| {
+                let r = &self.ranges[id];
+                *r.start() <= hi && lo <= *r.end()
+            }));
+
+            if lo < node.center {
+                stack.extend(node.left);
+            }
+            if hi > node.center {
+                stack.extend(node.right);
+            }
+        }
+
+        out
+    }
+
+    /// Number of ranges containing `point`
+    pub fn depth(&self, point: T) -> usize {
+        let started = self.starts.partition_point(|&s| s <= point);
+        let ended = self.ends.partition_point(|&e| e < point);
+        started - ended
+    }
+
+    /// All range boundaries in sweep order. At equal positions starts come before ends, since
+    /// ranges that merely touch still share that value.
+    pub fn sweep(&self) -> impl Iterator<Item = SweepEvent<T>> {
+        let mut events: Vec<SweepEvent<T>> = self.ranges.iter().enumerate()
+            .filter(|(_, r)| !r.is_empty())
+            .flat_map(|(id, r)| [
+                SweepEvent { pos: *r.start(), kind: SweepKind::Start, id },
+                SweepEvent { pos: *r.end(), kind: SweepKind::End, id },
+            ])
+            .collect();
+        events.sort_by_key(|e| (e.pos, e.kind, e.id));
+        events.into_iter()
+    }
+
+    /// The deepest overlap and the first value where it is reached, or None if there are no ranges
+    pub fn max_depth(&self) -> Option<(usize, T)> {
+        let mut depth = 0;
+        let mut best: Option<(usize, T)> = None;
+
+        for event in self.sweep() {
+            match event.kind {
+                SweepKind::Start => {
+                    depth += 1;
+                    if best.is_none_or(|(d, _)| depth > d) {
+                        best = Some((depth, event.pos));
+                    }
+                }
+                SweepKind::End => depth -= 1,
+            }
+        }
+
+        best
+    }
+}
+impl<T> FromIterator<RangeInclusive<T>> for IntervalTree<T> where T: PrimInt {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<T>>>(iter: I) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// day05's example ranges
+    fn example() -> IntervalTree<usize> {
+        [3..=5, 10..=14, 16..=20, 12..=18].into_iter().collect()
+    }
+
+    fn sorted(mut v: Vec<usize>) -> Vec<usize> {
+        v.sort();
+        v
+    }
+
+    #[test]
+    fn stabbing() {
+        let tree = example();
+        assert_eq!(sorted(tree.stab(1)), vec![]);
+        assert_eq!(sorted(tree.stab(5)), vec![0]);
+        assert_eq!(sorted(tree.stab(12)), vec![1, 3]);
+        assert_eq!(sorted(tree.stab(16)), vec![2, 3]);
+        assert_eq!(sorted(tree.stab(19)), vec![2]);
+
+        for p in 0..25 {
+            let brute: Vec<usize> = (0..tree.len()).filter(|&id| tree.range(id).contains(&p)).collect();
+            assert_eq!(sorted(tree.stab(p)), brute, "stab {}", p);
+            assert_eq!(tree.depth(p), brute.len(), "depth {}", p);
+        }
+    }
+
+    #[test]
+    fn overlaps() {
+        let tree = example();
+        assert_eq!(sorted(tree.overlapping(6..=9)), vec![]);
+        assert_eq!(sorted(tree.overlapping(5..=10)), vec![0, 1]);
+        assert_eq!(sorted(tree.overlapping(0..=100)), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn sweeping() {
+        let tree: IntervalTree<i32> = [0..=4, 4..=6, 2..=3, RangeInclusive::new(9, 8)].into_iter().collect();
+        let events: Vec<(i32, SweepKind)> = tree.sweep().map(|e| (e.pos, e.kind)).collect();
+        assert_eq!(events, vec![
+            (0, SweepKind::Start), (2, SweepKind::Start), (3, SweepKind::End),
+            (4, SweepKind::Start), (4, SweepKind::End), (6, SweepKind::End),
+        ]);
+
+        assert_eq!(tree.max_depth(), Some((2, 2)));
+        assert_eq!(example().max_depth(), Some((2, 12)));
+        assert_eq!(IntervalTree::<i32>::new(vec![]).max_depth(), None);
+    }
+}
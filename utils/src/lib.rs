@@ -1,6 +1,49 @@
-use std::{collections::{HashMap, HashSet}, hash::Hash, ops::{Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Sub, SubAssign}, slice::SliceIndex};
-
-use avl::AvlTreeSet;
+use std::{collections::{HashMap, HashSet}, fmt::Display, hash::Hash, ops::{Add, AddAssign, BitOr, BitOrAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign}, slice::SliceIndex, str::FromStr};
+
+use nom::{character::complete::{char, digit1}, combinator::{opt, recognize}, IResult, Parser};
+
+mod animator;
+mod automaton;
+mod bit_grid;
+mod color;
+mod color_scale;
+mod compactor;
+mod compressed_grid;
+mod cycle;
+mod geometry;
+mod gif;
+mod grid_diff;
+mod html;
+mod image;
+mod interval_set;
+mod interval_tree;
+mod point3;
+mod rect;
+mod region;
+mod render;
+mod svg;
+mod terminal;
+pub use animator::*;
+pub use automaton::*;
+pub use bit_grid::*;
+pub use color::*;
+pub use color_scale::*;
+pub use compactor::*;
+pub use compressed_grid::*;
+pub use cycle::*;
+pub use geometry::*;
+pub use gif::*;
+pub use grid_diff::*;
+pub use html::*;
+pub use image::*;
+pub use interval_set::*;
+pub use interval_tree::*;
+pub use point3::*;
+pub use rect::*;
+pub use region::*;
+pub use render::*;
+pub use svg::*;
+pub use terminal::*;
 
 #[inline]
 #[must_use]
@@ -10,105 +53,129 @@ pub fn parse_complete<'a, P, O>(parser: &mut P, input: &'a str) -> O where P: no
     parsed
 }
 
-pub fn colorize(input: &str, r: u8, g: u8, b: u8) -> String {
-    return "\x1b[38;2;".to_owned()+&r.to_string()+";"+&g.to_string()+";"+&b.to_string()+"m"+input+"\x1b[0m";
+/// Parses an optionally negative decimal integer of any type
+pub fn parse_int<T>(input: &str) -> IResult<&str, T> where T: FromStr {
+    recognize((opt(char('-')), digit1))
+        .map_res(str::parse)
+        .parse(input)
 }
 
-pub fn highlight(input: &str, actually: bool, r: u8, g: u8, b: u8) -> String {
-    if !actually {
+/// `input` in the given foreground colour, downgraded to the current [`color_mode`]
+pub fn colorize(input: &str, r: u8, g: u8, b: u8) -> String {
+    let mode = color_mode();
+    if !mode.is_colored() {
         return input.to_owned();
     }
-    return "\x1b[48;2;".to_owned()+&r.to_string()+";"+&g.to_string()+";"+&b.to_string()+"m"+input+"\x1b[0m";
-}
-
-const RESET: &str = "\x1b[0m";
-
-fn fg_string(c: Color) -> String {
-    return "\x1b[38;2;".to_owned() + &c.r.to_string() + ";" + &c.g.to_string() + ";" + &c.b.to_string() + "m";
+    mode.fg(Color::rgb(r, g, b)) + input + RESET
 }
 
-fn bg_string(c: Color) -> String {
-    return "\x1b[48;2;".to_owned() + &c.r.to_string() + ";" + &c.g.to_string() + ";" + &c.b.to_string() + "m";
+/// `input` on the given background colour if `actually`, downgraded to the current [`color_mode`]
+pub fn highlight(input: &str, actually: bool, r: u8, g: u8, b: u8) -> String {
+    let mode = color_mode();
+    if !actually || !mode.is_colored() {
+        return input.to_owned();
+    }
+    mode.bg(Color::rgb(r, g, b)) + input + RESET
 }
 
-#[derive(Copy, Clone, PartialEq, Eq)]
-pub struct Color {
-    pub r: u8,
-    pub g: u8,
-    pub b: u8
-}
-impl Color {
-    pub const WHITE: Color = Color::rgb(255, 255, 255);
-    pub const BLACK: Color = Color::rgb(0, 0, 0);
-
-    pub const fn rgb(r: u8, g: u8, b: u8) -> Color {
-        Color { r, g, b }
-    }
-
-    pub fn hsl(h: f32, s: f32, l: f32) -> Color {
-        let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
-        let h_prime = h / 60.0;
-        let x = c * (1.0 - (h_prime % 2.0 - 1.0).abs());
-
-        let (r1, g1, b1) = match h_prime as u32 {
-            0 => (c, x, 0.0),
-            1 => (x, c, 0.0),
-            2 => (0.0, c, x),
-            3 => (0.0, x, c),
-            4 => (x, 0.0, c),
-            5 => (c, 0.0, x),
-            _ => (0.0, 0.0, 0.0),
-        };
+/// Text attributes, combined with `|`
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Attrs(u8);
+impl Attrs {
+    pub const NONE: Attrs = Attrs(0);
+    pub const BOLD: Attrs = Attrs(1 << 0);
+    pub const DIM: Attrs = Attrs(1 << 1);
+    pub const UNDERLINE: Attrs = Attrs(1 << 2);
+    pub const REVERSE: Attrs = Attrs(1 << 3);
 
-        let m = l - c / 2.0;
+    /// SGR parameter of every attribute
+    const CODES: [(Attrs, u8); 4] = [(Attrs::BOLD, 1), (Attrs::DIM, 2), (Attrs::UNDERLINE, 4), (Attrs::REVERSE, 7)];
 
-        Color {
-            r: ((r1 + m) * 255.0).round() as u8,
-            g: ((g1 + m) * 255.0).round() as u8,
-            b: ((b1 + m) * 255.0).round() as u8,
-        }
+    pub fn contains(self, other: Attrs) -> bool {
+        self.0 & other.0 == other.0
     }
 
-    pub fn random_from_seed(seed: usize) -> Color {
-        // multiply by a large prime to simulate randomness
-        let seed = seed.wrapping_mul(10722542609); 
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
 
-        let hue = (seed % 360) as f32;
-        let saturation = 0.9;
-        let lightness = 0.6;
+    /// Escape sequence turning on all contained attributes
+    pub fn escape(self) -> String {
+        Self::CODES.iter()
+            .filter(|(attr, _)| self.contains(*attr))
+            .map(|(_, code)| format!("\x1b[{}m", code))
+            .collect()
+    }
+}
+impl BitOr for Attrs {
+    type Output = Attrs;
 
-        Color::hsl(hue, saturation, lightness)
+    fn bitor(self, rhs: Attrs) -> Attrs {
+        Attrs(self.0 | rhs.0)
+    }
+}
+impl BitOrAssign for Attrs {
+    fn bitor_assign(&mut self, rhs: Attrs) {
+        self.0 |= rhs.0;
     }
 }
 
+/// Which side keeps its colours when two styles are merged
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Precedence {
+    /// the existing style wins; the other only fills in missing colours
+    Base,
+    /// the merged-in style wins wherever it has a colour
+    Overlay,
+}
+
 #[derive(Default, Copy, Clone, PartialEq, Eq)]
 pub struct Style {
     pub fg: Option<Color>,
     pub bg: Option<Color>,
+    pub attrs: Attrs,
 }
 impl Style {
     pub fn fg(c: Option<Color>) -> Style {
         Style {
             fg: c,
-            bg: None
+            ..Default::default()
         }
     }
 
     pub fn bg(c: Option<Color>) -> Style {
         Style {
-            fg: None,
-            bg: c
+            bg: c,
+            ..Default::default()
         }
     }
 
-    pub fn merge(&mut self, other: &Style) {
-        if let None = self.fg {
-            self.fg = other.fg;
+    pub fn attrs(attrs: Attrs) -> Style {
+        Style {
+            attrs,
+            ..Default::default()
         }
+    }
 
-        if let None = self.bg {
-            self.bg = other.bg;
-        }
+    pub fn with_attrs(mut self, attrs: Attrs) -> Style {
+        self.attrs |= attrs;
+        self
+    }
+
+    /// Fill in colours this style lacks from `other`; attributes are always combined
+    pub fn merge(&mut self, other: &Style) {
+        self.merge_with(other, Precedence::Base);
+    }
+
+    pub fn merge_with(&mut self, other: &Style, precedence: Precedence) {
+        let pick = |mine: Option<Color>, theirs: Option<Color>| match precedence {
+            Precedence::Base => mine.or(theirs),
+            Precedence::Overlay => theirs.or(mine),
+        };
+
+        self.fg = pick(self.fg, other.fg);
+        self.bg = pick(self.bg, other.bg);
+        self.attrs |= other.attrs;
     }
 }
 
@@ -126,42 +193,65 @@ impl StyledChar {
     }
 }
 
+/// A sparse layer of styles to stack on top of a grid, e.g. a path or a visited set
+#[derive(Default, Clone)]
+pub struct Overlay {
+    cells: HashMap<Point<usize>, Style>,
+}
+impl Overlay {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The same style on every given cell
+    pub fn uniform<I>(cells: I, style: Style) -> Self where I: IntoIterator<Item = Point<usize>> {
+        let mut overlay = Self::new();
+        cells.into_iter().for_each(|pt| overlay.add(pt, style));
+        overlay
+    }
+
+    /// Style `pt`, on top of anything already set for it in this layer
+    pub fn add(&mut self, pt: Point<usize>, style: Style) {
+        self.cells.entry(pt).or_default().merge_with(&style, Precedence::Overlay);
+    }
+
+    pub fn get(&self, pt: Point<usize>) -> Option<&Style> {
+        self.cells.get(&pt)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Point<usize>, &Style)> {
+        self.cells.iter().map(|(&pt, style)| (pt, style))
+    }
+}
+
 pub trait StyleUtil {
     fn merge_style(&mut self, row: usize, column: usize, style: &Style);
+
+    /// Merge every cell of `overlay` in; cells outside the grid are ignored
+    fn apply_overlay(&mut self, overlay: &Overlay, precedence: Precedence);
+
+    /// Copy with `layers` applied bottom to top, so with [`Precedence::Overlay`] the last layer
+    /// is the one on top
+    fn flatten(&self, layers: &[Overlay], precedence: Precedence) -> Vec<Vec<StyledChar>>;
 }
 
 impl StyleUtil for Vec<Vec<StyledChar>> {
     fn merge_style(&mut self, row: usize, column: usize, style: &Style) {
         self[row][column].style.merge(style);
     }
-}
-
-pub fn print_grid(input: &Vec<Vec<StyledChar>>) {
-    for r in 0..input.len() {
-        let row = &input[r];
-        let mut last_style = Style::default();
-
-        for tile in row {
-            if tile.style != last_style {
-                last_style = tile.style;
-                print!("{}", RESET);
 
-                if let Some(c) = tile.style.fg {
-                    print!("{}", fg_string(c));
-                }
-
-                if let Some(c) = tile.style.bg {
-                    print!("{}", bg_string(c));
-                }
+    fn apply_overlay(&mut self, overlay: &Overlay, precedence: Precedence) {
+        for (pt, style) in overlay.iter() {
+            if let Some(cell) = self.get_mut(pt.y).and_then(|row| row.get_mut(pt.x)) {
+                cell.style.merge_with(style, precedence);
             }
-            print!("{}", tile.chr);
-        }
-
-        if let (None, None) = (last_style.fg, last_style.bg) {} else {
-            print!("{}", RESET);
         }
+    }
 
-        print!("\n");
+    fn flatten(&self, layers: &[Overlay], precedence: Precedence) -> Vec<Vec<StyledChar>> {
+        let mut out = self.clone();
+        layers.iter().for_each(|layer| out.apply_overlay(layer, precedence));
+        out
     }
 }
 
@@ -197,18 +287,89 @@ impl<T> GridMap<T> for Vec<Vec<T>> {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+/// |a - b| without going through a signed type
+pub(crate) fn abs_diff<T>(a: T, b: T) -> T where T: PartialOrd + Sub<Output = T> {
+    if a > b { a - b } else { b - a }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Point<T> {
     pub x: T,
     pub y: T
 }
 impl<T> Point<T> {
-    pub fn map<U>(self, mapper: fn(T) -> U) -> Point<U> {
+    pub fn map<U, F>(self, mut mapper: F) -> Point<U> where F: FnMut(T) -> U {
         Point {
             x: mapper(self.x),
             y: mapper(self.y)
         }
     }
+
+    /// Combine two points component-wise
+    pub fn zip_with<U, V, F>(self, other: Point<U>, mut f: F) -> Point<V> where F: FnMut(T, U) -> V {
+        Point {
+            x: f(self.x, other.x),
+            y: f(self.y, other.y)
+        }
+    }
+}
+impl<T> Point<T> where T: PartialOrd + Sub<Output = T> + Add<Output = T> + Copy {
+    pub fn manhattan(self, other: Self) -> T {
+        abs_diff(self.x, other.x) + abs_diff(self.y, other.y)
+    }
+
+    pub fn chebyshev(self, other: Self) -> T {
+        let dx = abs_diff(self.x, other.x);
+        let dy = abs_diff(self.y, other.y);
+        if dx > dy { dx } else { dy }
+    }
+}
+impl<T> Point<T> where T: FromStr {
+    /// Parses `x,y`
+    pub fn parse(input: &str) -> IResult<&str, Self> {
+        (parse_int, char(','), parse_int)
+            .map(|(x, _, y)| Self { x, y })
+            .parse(input)
+    }
+}
+impl Point<usize> {
+    /// Offset by a signed amount, or None if either coordinate would leave usize
+    pub fn checked_add_signed(self, offset: Point<isize>) -> Option<Self> {
+        Some(Point {
+            x: self.x.checked_add_signed(offset.x)?,
+            y: self.y.checked_add_signed(offset.y)?
+        })
+    }
+
+    pub fn checked_signed(self) -> Option<Point<isize>> {
+        Some(Point {
+            x: self.x.try_into().ok()?,
+            y: self.y.try_into().ok()?
+        })
+    }
+}
+impl Point<isize> {
+    pub fn checked_unsigned(self) -> Option<Point<usize>> {
+        Some(Point {
+            x: self.x.try_into().ok()?,
+            y: self.y.try_into().ok()?
+        })
+    }
+}
+impl<T> Neg for Point<T> where T: Neg<Output = T> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Point {
+            x: Neg::neg(self.x),
+            y: Neg::neg(self.y)
+        }
+    }
+}
+impl<T> Display for Point<T> where T: Display {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
 }
 impl<T> Add for Point<T> where T: Add<Output = T> {
     type Output = Self;
@@ -307,57 +468,6 @@ impl<P, V> IndexMut<Point<P>> for Vec<Vec<V>> where
     }
 }
 
-#[derive(Debug)]
-pub struct Compactor<T> where T: std::cmp::Ord {
-    xs: AvlTreeSet<T>,
-    ys: AvlTreeSet<T>
-}
-impl<T> Default for Compactor<T> where T: std::cmp::Ord + num_traits::Num + AddAssign<T> + Copy  {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-impl<T> Compactor<T> where T: std::cmp::Ord + num_traits::Num + AddAssign<T> + Copy {
-    pub fn new() -> Self {
-        Self { xs: AvlTreeSet::new(), ys: AvlTreeSet::new() }
-    }
-
-    pub fn add_key_point(&mut self, pt: Point<T>) {
-        self.xs.insert(pt.x);
-        self.ys.insert(pt.y);
-    }
-
-    pub fn width(&self) -> usize {
-        self.xs.len()
-    }
-
-    pub fn height(&self) -> usize {
-        self.ys.len()
-    }
-
-    pub fn compact(&self, pt: Point<T>) -> Point<T> {
-        let mut x = T::zero();
-        for &key_x in &self.xs {
-            if key_x > pt.x {
-                break;
-            }
-
-            x += T::one();
-        }
-
-        let mut y = T::zero();
-        for &key_y in &self.ys {
-            if key_y > pt.y {
-                break;
-            }
-
-            y += T::one();
-        }
-
-        Point { x, y }
-    }
-}
-
 pub trait DijkstraNode<T> where Self: PartialEq + Eq + Hash + Clone {
     /// Returns a vector of (node, distance) pairs
     fn get_connected(&self, context: &T) -> Vec<(Self, usize)> where Self: Sized;
@@ -471,10 +581,50 @@ mod tests {
     }
     impl DijkstraNode<()> for T {
         fn get_connected(&self, _: &()) -> Vec<(Self, usize)> where Self: Sized {
-            return self.value();
+            self.value()
         }
     }
 
+    #[test]
+    fn style_precedence() {
+        let (red, blue) = (Some(Color::rgb(255, 0, 0)), Some(Color::rgb(0, 0, 255)));
+        let base = Style { fg: red, bg: None, attrs: Attrs::BOLD };
+        let overlay = Style { fg: blue, bg: blue, attrs: Attrs::UNDERLINE };
+
+        let mut kept = base;
+        kept.merge(&overlay);
+        assert!(kept == Style { fg: red, bg: blue, attrs: Attrs::BOLD | Attrs::UNDERLINE });
+
+        let mut replaced = base;
+        replaced.merge_with(&overlay, Precedence::Overlay);
+        assert!(replaced == Style { fg: blue, bg: blue, attrs: Attrs::BOLD | Attrs::UNDERLINE });
+    }
+
+    #[test]
+    fn point_api() {
+        let a = Point { x: 3_usize, y: 7 };
+        let b = Point { x: 5_usize, y: 2 };
+        assert_eq!(a.manhattan(b), 7);
+        assert_eq!(a.chebyshev(b), 5);
+        assert_eq!(a.zip_with(b, usize::max), Point { x: 5, y: 7 });
+
+        let offset = 10;
+        assert_eq!(a.map(|v| v + offset), Point { x: 13, y: 17 });
+
+        assert_eq!(a.checked_add_signed(Point { x: -3, y: 1 }), Some(Point { x: 0, y: 8 }));
+        assert_eq!(a.checked_add_signed(Point { x: -4, y: 1 }), None);
+        assert_eq!(a.checked_signed().map(|p| -p), Some(Point { x: -3, y: -7 }));
+        assert_eq!(Point { x: -1_isize, y: 0 }.checked_unsigned(), None);
+
+        let parsed: Point<i64> = parse_complete(&mut Point::parse, "-12,4");
+        assert_eq!(parsed, Point { x: -12, y: 4 });
+        assert_eq!(parsed.to_string(), "-12,4");
+
+        let set: HashSet<Point<usize>> = [a, b, a].into_iter().collect();
+        assert_eq!(set.len(), 2);
+        assert!(Point { x: 1, y: 9 } < Point { x: 2, y: 0 });
+    }
+
     #[test]
     fn dijkstra_search() {
         fn hlt(node: &T) -> bool { *node == T::E }
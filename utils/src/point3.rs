@@ -0,0 +1,209 @@
+use std::{fmt::Display, ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign}, str::FromStr};
+
+use nom::{character::complete::char, IResult, Parser};
+
+use crate::{abs_diff, parse_int};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T
+}
+
+impl<T> Point3<T> {
+    pub fn map<U, F>(self, mut mapper: F) -> Point3<U> where F: FnMut(T) -> U {
+        Point3 {
+            x: mapper(self.x),
+            y: mapper(self.y),
+            z: mapper(self.z)
+        }
+    }
+
+    /// Combine two points component-wise
+    pub fn zip_with<U, V, F>(self, other: Point3<U>, mut f: F) -> Point3<V> where F: FnMut(T, U) -> V {
+        Point3 {
+            x: f(self.x, other.x),
+            y: f(self.y, other.y),
+            z: f(self.z, other.z)
+        }
+    }
+}
+impl<T> Point3<T> where T: PartialOrd + Sub<Output = T> + Add<Output = T> + Copy {
+    pub fn manhattan(self, other: Self) -> T {
+        abs_diff(self.x, other.x) + abs_diff(self.y, other.y) + abs_diff(self.z, other.z)
+    }
+
+    pub fn chebyshev(self, other: Self) -> T {
+        let dx = abs_diff(self.x, other.x);
+        let dy = abs_diff(self.y, other.y);
+        let dz = abs_diff(self.z, other.z);
+
+        let dxy = if dx > dy { dx } else { dy };
+        if dxy > dz { dxy } else { dz }
+    }
+}
+impl<T> Point3<T> where T: PartialOrd + Sub<Output = T> + Add<Output = T> + Mul<Output = T> + Copy {
+    /// Squared euclidean distance
+    pub fn dist_sq(self, other: Self) -> T {
+        let dx = abs_diff(self.x, other.x);
+        let dy = abs_diff(self.y, other.y);
+        let dz = abs_diff(self.z, other.z);
+
+        dx*dx + dy*dy + dz*dz
+    }
+}
+impl<T> Point3<T> where T: FromStr {
+    /// Parses `x,y,z`
+    pub fn parse(input: &str) -> IResult<&str, Self> {
+        (parse_int, char(','), parse_int, char(','), parse_int)
+            .map(|(x, _, y, _, z)| Self { x, y, z })
+            .parse(input)
+    }
+}
+
+impl<T> Add for Point3<T> where T: Add<Output = T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Point3 {
+            x: Add::add(self.x, rhs.x),
+            y: Add::add(self.y, rhs.y),
+            z: Add::add(self.z, rhs.z)
+        }
+    }
+}
+impl<T> AddAssign for Point3<T> where T: AddAssign {
+    fn add_assign(&mut self, rhs: Self) {
+        AddAssign::add_assign(&mut self.x, rhs.x);
+        AddAssign::add_assign(&mut self.y, rhs.y);
+        AddAssign::add_assign(&mut self.z, rhs.z);
+    }
+}
+impl<T> Sub for Point3<T> where T: Sub<Output = T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Point3 {
+            x: Sub::sub(self.x, rhs.x),
+            y: Sub::sub(self.y, rhs.y),
+            z: Sub::sub(self.z, rhs.z)
+        }
+    }
+}
+impl<T> SubAssign for Point3<T> where T: SubAssign {
+    fn sub_assign(&mut self, rhs: Self) {
+        SubAssign::sub_assign(&mut self.x, rhs.x);
+        SubAssign::sub_assign(&mut self.y, rhs.y);
+        SubAssign::sub_assign(&mut self.z, rhs.z);
+    }
+}
+impl<T> Mul<T> for Point3<T> where T: Mul<Output = T> + Copy {
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        Self {
+            x: Mul::mul(self.x, rhs),
+            y: Mul::mul(self.y, rhs),
+            z: Mul::mul(self.z, rhs)
+        }
+    }
+}
+impl<T> MulAssign<T> for Point3<T> where T: MulAssign + Copy {
+    fn mul_assign(&mut self, rhs: T) {
+        MulAssign::mul_assign(&mut self.x, rhs);
+        MulAssign::mul_assign(&mut self.y, rhs);
+        MulAssign::mul_assign(&mut self.z, rhs);
+    }
+}
+impl<T> Div<T> for Point3<T> where T: Div<Output = T> + Copy {
+    type Output = Self;
+
+    fn div(self, rhs: T) -> Self::Output {
+        Self {
+            x: Div::div(self.x, rhs),
+            y: Div::div(self.y, rhs),
+            z: Div::div(self.z, rhs)
+        }
+    }
+}
+impl<T> DivAssign<T> for Point3<T> where T: DivAssign + Copy {
+    fn div_assign(&mut self, rhs: T) {
+        DivAssign::div_assign(&mut self.x, rhs);
+        DivAssign::div_assign(&mut self.y, rhs);
+        DivAssign::div_assign(&mut self.z, rhs);
+    }
+}
+
+impl<T> Neg for Point3<T> where T: Neg<Output = T> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Point3 {
+            x: Neg::neg(self.x),
+            y: Neg::neg(self.y),
+            z: Neg::neg(self.z)
+        }
+    }
+}
+
+impl<T> Display for Point3<T> where T: Display {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{},{},{}", self.x, self.y, self.z)
+    }
+}
+
+impl<T> From<(T, T, T)> for Point3<T> {
+    fn from(value: (T, T, T)) -> Self {
+        Self {
+            x: value.0,
+            y: value.1,
+            z: value.2
+        }
+    }
+}
+
+impl<T> From<Point3<T>> for (T, T, T) {
+    fn from(value: Point3<T>) -> Self {
+        (value.x, value.y, value.z)
+    }
+}
+
+impl<T> From<[T; 3]> for Point3<T> {
+    fn from([x, y, z]: [T; 3]) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl<T> From<Point3<T>> for [T; 3] {
+    fn from(value: Point3<T>) -> Self {
+        [value.x, value.y, value.z]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_complete;
+
+    #[test]
+    fn distances() {
+        let a = Point3 { x: 162_usize, y: 817, z: 812 };
+        let b = Point3 { x: 425_usize, y: 690, z: 689 };
+
+        assert_eq!(a.dist_sq(b), 263*263 + 127*127 + 123*123);
+        assert_eq!(a.manhattan(b), 263 + 127 + 123);
+        assert_eq!(a.chebyshev(b), 263);
+        assert_eq!(a.dist_sq(b), b.dist_sq(a));
+    }
+
+    #[test]
+    fn ops_and_parse() {
+        let a: Point3<i32> = parse_complete(&mut Point3::parse, "1,-2,3");
+        assert_eq!(a, Point3 { x: 1, y: -2, z: 3 });
+        assert_eq!(a + a * 2, Point3 { x: 3, y: -6, z: 9 });
+        assert_eq!(a - a, Point3 { x: 0, y: 0, z: 0 });
+        assert_eq!(a.to_string(), "1,-2,3");
+        assert_eq!(a.map(|v| v.unsigned_abs()), Point3 { x: 1_u32, y: 2, z: 3 });
+    }
+}
@@ -0,0 +1,227 @@
+use std::iter::successors;
+
+use num_traits::PrimInt;
+
+use crate::Point;
+
+/// Inclusive range `lo..=hi` stepped by one, for any integer type
+fn steps<T: PrimInt>(lo: T, hi: T) -> impl Iterator<Item = T> + Clone {
+    successors((lo <= hi).then_some(lo), move |&v| (v < hi).then(|| v + T::one()))
+}
+
+/// Axis-aligned rectangle; both corners are inclusive
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Rect<T> {
+    pub min: Point<T>,
+    pub max: Point<T>,
+}
+impl<T> Rect<T> where T: PrimInt {
+    /// Rectangle spanned by any two opposite corners
+    pub fn from_corners(a: Point<T>, b: Point<T>) -> Self {
+        Self {
+            min: Point { x: a.x.min(b.x), y: a.y.min(b.y) },
+            max: Point { x: a.x.max(b.x), y: a.y.max(b.y) },
+        }
+    }
+
+    /// Smallest rectangle containing every point, or None if there are none
+    pub fn bounding<I>(points: I) -> Option<Self> where I: IntoIterator<Item = Point<T>> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(Self::from_corners(first, first), |rect, pt| rect.expanded_to(pt)))
+    }
+
+    /// max.x - min.x
+    pub fn width(&self) -> T {
+        self.max.x - self.min.x
+    }
+
+    /// max.y - min.y
+    pub fn height(&self) -> T {
+        self.max.y - self.min.y
+    }
+
+    /// Number of contained lattice points, counting both edges
+    pub fn area_inclusive(&self) -> T {
+        (self.width() + T::one()) * (self.height() + T::one())
+    }
+
+    /// Geometric area, treating the corners as points rather than tiles
+    pub fn area_exclusive(&self) -> T {
+        self.width() * self.height()
+    }
+
+    pub fn contains(&self, pt: Point<T>) -> bool {
+        self.min.x <= pt.x && pt.x <= self.max.x && self.min.y <= pt.y && pt.y <= self.max.y
+    }
+
+    pub fn contains_rect(&self, other: &Self) -> bool {
+        self.contains(other.min) && self.contains(other.max)
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let min = Point { x: self.min.x.max(other.min.x), y: self.min.y.max(other.min.y) };
+        let max = Point { x: self.max.x.min(other.max.x), y: self.max.y.min(other.max.y) };
+
+        if min.x <= max.x && min.y <= max.y {
+            Some(Self { min, max })
+        } else {
+            None
+        }
+    }
+
+    /// Smallest rectangle containing both
+    pub fn hull(&self, other: &Self) -> Self {
+        Self {
+            min: Point { x: self.min.x.min(other.min.x), y: self.min.y.min(other.min.y) },
+            max: Point { x: self.max.x.max(other.max.x), y: self.max.y.max(other.max.y) },
+        }
+    }
+
+    pub fn expanded_to(&self, pt: Point<T>) -> Self {
+        self.hull(&Self::from_corners(pt, pt))
+    }
+
+    /// All contained points, in row-major order
+    pub fn points(&self) -> impl Iterator<Item = Point<T>> {
+        let xs = steps(self.min.x, self.max.x);
+        steps(self.min.y, self.max.y).flat_map(move |y| xs.clone().map(move |x| Point { x, y }))
+    }
+}
+
+/// Axis-aligned box in N dimensions; both corners are inclusive
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct BoxN<T, const N: usize> {
+    pub min: [T; N],
+    pub max: [T; N],
+}
+pub type Box3<T> = BoxN<T, 3>;
+
+impl<T, const N: usize> BoxN<T, N> where T: PrimInt {
+    pub fn from_corners(a: [T; N], b: [T; N]) -> Self {
+        Self {
+            min: std::array::from_fn(|i| a[i].min(b[i])),
+            max: std::array::from_fn(|i| a[i].max(b[i])),
+        }
+    }
+
+    pub fn bounding<I>(points: I) -> Option<Self> where I: IntoIterator<Item = [T; N]> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(Self::from_corners(first, first), |b, pt| b.hull(&Self::from_corners(pt, pt))))
+    }
+
+    /// max - min along one axis
+    pub fn extent(&self, axis: usize) -> T {
+        self.max[axis] - self.min[axis]
+    }
+
+    /// Number of contained lattice points
+    pub fn volume_inclusive(&self) -> T {
+        (0..N).fold(T::one(), |v, axis| v * (self.extent(axis) + T::one()))
+    }
+
+    pub fn volume_exclusive(&self) -> T {
+        (0..N).fold(T::one(), |v, axis| v * self.extent(axis))
+    }
+
+    pub fn contains(&self, pt: &[T; N]) -> bool {
+        (0..N).all(|i| self.min[i] <= pt[i] && pt[i] <= self.max[i])
+    }
+
+    pub fn contains_box(&self, other: &Self) -> bool {
+        self.contains(&other.min) && self.contains(&other.max)
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let min: [T; N] = std::array::from_fn(|i| self.min[i].max(other.min[i]));
+        let max: [T; N] = std::array::from_fn(|i| self.max[i].min(other.max[i]));
+
+        if (0..N).all(|i| min[i] <= max[i]) {
+            Some(Self { min, max })
+        } else {
+            None
+        }
+    }
+
+    pub fn hull(&self, other: &Self) -> Self {
+        Self {
+            min: std::array::from_fn(|i| self.min[i].min(other.min[i])),
+            max: std::array::from_fn(|i| self.max[i].max(other.max[i])),
+        }
+    }
+
+    /// All contained points; the last axis varies fastest
+    pub fn points(&self) -> impl Iterator<Item = [T; N]> {
+        let (min, max) = (self.min, self.max);
+        successors(Some(min), move |&pt| {
+            let mut next = pt;
+            for axis in (0..N).rev() {
+                if next[axis] < max[axis] {
+                    next[axis] = next[axis] + T::one();
+                    return Some(next);
+                }
+                next[axis] = min[axis];
+            }
+            None
+        })
+    }
+}
+
+impl<T> From<Rect<T>> for BoxN<T, 2> {
+    fn from(value: Rect<T>) -> Self {
+        Self {
+            min: [value.min.x, value.min.y],
+            max: [value.max.x, value.max.y],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pt(x: i32, y: i32) -> Point<i32> {
+        Point { x, y }
+    }
+
+    #[test]
+    fn rect_ops() {
+        let a = Rect::from_corners(pt(4, 1), pt(0, 3));
+        assert_eq!(a.min, pt(0, 1));
+        assert_eq!(a.max, pt(4, 3));
+        assert_eq!(a.area_inclusive(), 15);
+        assert_eq!(a.area_exclusive(), 8);
+
+        let b = Rect::from_corners(pt(3, 2), pt(6, 6));
+        assert_eq!(a.intersection(&b), Some(Rect::from_corners(pt(3, 2), pt(4, 3))));
+        assert_eq!(a.hull(&b), Rect::from_corners(pt(0, 1), pt(6, 6)));
+        assert_eq!(a.intersection(&Rect::from_corners(pt(5, 0), pt(6, 6))), None);
+
+        assert!(a.contains(pt(4, 3)));
+        assert!(!a.contains(pt(5, 3)));
+        assert_eq!(Rect::bounding([pt(1, 5), pt(3, -1), pt(2, 2)]), Some(Rect::from_corners(pt(1, -1), pt(3, 5))));
+    }
+
+    #[test]
+    fn rect_points() {
+        let r = Rect::from_corners(pt(1, 1), pt(2, 3));
+        let points: Vec<_> = r.points().collect();
+        assert_eq!(points, vec![pt(1, 1), pt(2, 1), pt(1, 2), pt(2, 2), pt(1, 3), pt(2, 3)]);
+        assert_eq!(points.len() as i32, r.area_inclusive());
+    }
+
+    #[test]
+    fn box_ops() {
+        let a: Box3<u32> = BoxN::from_corners([2, 0, 5], [0, 3, 4]);
+        assert_eq!(a.min, [0, 0, 4]);
+        assert_eq!(a.volume_inclusive(), 3 * 4 * 2);
+        assert_eq!(a.volume_exclusive(), 2 * 3);
+        assert_eq!(a.points().count(), 24);
+        assert!(a.points().all(|p| a.contains(&p)));
+
+        let b = BoxN::from_corners([1, 1, 1], [9, 9, 4]);
+        assert_eq!(a.intersection(&b), Some(BoxN::from_corners([1, 1, 4], [2, 3, 4])));
+        assert!(a.hull(&b).contains_box(&a));
+    }
+}
@@ -0,0 +1,262 @@
+use std::collections::VecDeque;
+
+use crate::{Point, Rect};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Connectivity {
+    /// orthogonal neighbours only
+    Four,
+    /// orthogonal and diagonal neighbours
+    Eight,
+}
+impl Connectivity {
+    const FOUR: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+    const EIGHT: [(isize, isize); 8] = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+
+    /// (dx, dy) offsets of all neighbours
+    pub fn offsets(self) -> &'static [(isize, isize)] {
+        match self {
+            Connectivity::Four => &Self::FOUR,
+            Connectivity::Eight => &Self::EIGHT,
+        }
+    }
+
+    /// All in-bounds neighbours of `pt` in a `width` x `height` grid
+    pub fn neighbours(self, pt: Point<usize>, width: usize, height: usize) -> impl Iterator<Item = Point<usize>> {
+        self.offsets().iter()
+            .filter_map(move |&offset| pt.checked_add_signed(offset.into()))
+            .filter(move |n| n.x < width && n.y < height)
+    }
+}
+
+fn dimensions<T>(grid: &[Vec<T>]) -> (usize, usize) {
+    (grid.first().map_or(0, Vec::len), grid.len())
+}
+
+/// Breadth-first search from `seeds`. `accept` is called once per candidate cell and must
+/// return true (and remember that it did) exactly once for every cell that joins the fill.
+fn bfs<A>(width: usize, height: usize, seeds: impl IntoIterator<Item = Point<usize>>, connectivity: Connectivity, mut accept: A) -> Vec<Point<usize>>
+    where A: FnMut(Point<usize>) -> bool
+{
+    let mut queue: VecDeque<Point<usize>> = seeds.into_iter().filter(|&pt| accept(pt)).collect();
+    let mut filled = vec![];
+
+    while let Some(pt) = queue.pop_front() {
+        filled.push(pt);
+
+        for next in connectivity.neighbours(pt, width, height) {
+            if accept(next) {
+                queue.push_back(next);
+            }
+        }
+    }
+
+    filled
+}
+
+/// A connected set of grid cells
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    pub label: usize,
+    pub cells: Vec<Point<usize>>,
+    /// number of cell edges that border a cell outside the region (or the grid edge)
+    pub perimeter: usize,
+    pub bounds: Rect<usize>,
+}
+impl Region {
+    fn new(label: usize, cells: Vec<Point<usize>>, width: usize, height: usize, is_member: impl Fn(Point<usize>) -> bool) -> Self {
+        let bounds = Rect::bounding(cells.iter().copied()).expect("region must not be empty");
+        let mut perimeter = 0;
+
+        for &pt in &cells {
+            let inner_edges = Connectivity::Four.neighbours(pt, width, height)
+                .filter(|&n| is_member(n))
+                .count();
+            perimeter += 4 - inner_edges;
+        }
+
+        Self { label, cells, perimeter, bounds }
+    }
+
+    pub fn area(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn bounding_box(&self) -> Rect<usize> {
+        self.bounds
+    }
+}
+
+/// Fill outwards from `start` over all cells matching `pred`.
+/// Returns None if `start` itself does not match.
+pub fn flood_fill<T, P>(grid: &[Vec<T>], start: Point<usize>, connectivity: Connectivity, pred: P) -> Option<Region>
+    where P: Fn(&T) -> bool
+{
+    let (width, height) = dimensions(grid);
+    let mut visited = vec![vec![false; width]; height];
+
+    let cells = bfs(width, height, [start], connectivity, |pt| {
+        if visited[pt.y][pt.x] || !pred(&grid[pt.y][pt.x]) {
+            return false;
+        }
+        visited[pt.y][pt.x] = true;
+        true
+    });
+
+    if cells.is_empty() {
+        return None;
+    }
+
+    Some(Region::new(0, cells, width, height, |pt| visited[pt.y][pt.x]))
+}
+
+#[derive(Debug, Clone)]
+pub struct Labeling {
+    /// label of every cell, or None if the cell is not part of any region
+    pub labels: Vec<Vec<Option<usize>>>,
+    /// regions, indexed by label
+    pub regions: Vec<Region>,
+}
+impl Labeling {
+    pub fn label_at(&self, pt: Point<usize>) -> Option<usize> {
+        self.labels[pt.y][pt.x]
+    }
+
+    pub fn region_at(&self, pt: Point<usize>) -> Option<&Region> {
+        self.label_at(pt).map(|label| &self.regions[label])
+    }
+}
+
+fn label_with<T, S>(grid: &[Vec<T>], connectivity: Connectivity, include: impl Fn(&T) -> bool, same: S) -> Labeling
+    where S: Fn(&T, &T) -> bool
+{
+    let (width, height) = dimensions(grid);
+    let mut labels: Vec<Vec<Option<usize>>> = vec![vec![None; width]; height];
+    let mut regions = vec![];
+
+    for y in 0..height {
+        for x in 0..width {
+            if labels[y][x].is_some() || !include(&grid[y][x]) {
+                continue;
+            }
+
+            let label = regions.len();
+            let seed = &grid[y][x];
+            let cells = bfs(width, height, [Point { x, y }], connectivity, |pt| {
+                let tile = &grid[pt.y][pt.x];
+                if labels[pt.y][pt.x].is_some() || !include(tile) || !same(seed, tile) {
+                    return false;
+                }
+                labels[pt.y][pt.x] = Some(label);
+                true
+            });
+
+            regions.push(Region::new(label, cells, width, height, |pt| labels[pt.y][pt.x] == Some(label)));
+        }
+    }
+
+    Labeling { labels, regions }
+}
+
+/// Label the connected components formed by cells matching `pred`
+pub fn label_regions<T, P>(grid: &[Vec<T>], connectivity: Connectivity, pred: P) -> Labeling
+    where P: Fn(&T) -> bool
+{
+    label_with(grid, connectivity, pred, |_, _| true)
+}
+
+/// Label the connected components of equal-valued cells; every cell gets a label
+pub fn label_regions_by<T, S>(grid: &[Vec<T>], connectivity: Connectivity, same: S) -> Labeling
+    where S: Fn(&T, &T) -> bool
+{
+    label_with(grid, connectivity, |_| true, same)
+}
+
+/// Mark every `passable` cell that can be reached from the grid border.
+/// Passable cells that are not marked are enclosed by impassable ones.
+pub fn fill_exterior<T, P>(grid: &[Vec<T>], connectivity: Connectivity, passable: P) -> Vec<Vec<bool>>
+    where P: Fn(&T) -> bool
+{
+    let (width, height) = dimensions(grid);
+    let mut exterior = vec![vec![false; width]; height];
+    if width == 0 || height == 0 {
+        return exterior;
+    }
+
+    let border = (0..width).flat_map(|x| [Point { x, y: 0 }, Point { x, y: height - 1 }])
+        .chain((0..height).flat_map(|y| [Point { x: 0, y }, Point { x: width - 1, y }]));
+
+    bfs(width, height, border, connectivity, |pt| {
+        if exterior[pt.y][pt.x] || !passable(&grid[pt.y][pt.x]) {
+            return false;
+        }
+        exterior[pt.y][pt.x] = true;
+        true
+    });
+
+    exterior
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(input: &str) -> Vec<Vec<char>> {
+        crate::parse_grid(input)
+    }
+
+    #[test]
+    fn flood_fill_connectivity() {
+        let g = grid("
+#..
+.#.
+..#
+");
+        let four = flood_fill(&g, Point { x: 0, y: 0 }, Connectivity::Four, |&c| c == '#').unwrap();
+        assert_eq!(four.area(), 1);
+        assert_eq!(four.perimeter, 4);
+
+        let eight = flood_fill(&g, Point { x: 0, y: 0 }, Connectivity::Eight, |&c| c == '#').unwrap();
+        assert_eq!(eight.area(), 3);
+        assert_eq!(eight.perimeter, 12);
+        assert_eq!(eight.bounding_box(), Rect::from_corners(Point { x: 0, y: 0 }, Point { x: 2, y: 2 }));
+
+        assert!(flood_fill(&g, Point { x: 1, y: 0 }, Connectivity::Four, |&c| c == '#').is_none());
+    }
+
+    #[test]
+    fn labeling() {
+        let g = grid("
+AAB
+ABB
+CCB
+");
+        let labeling = label_regions_by(&g, Connectivity::Four, |a, b| a == b);
+        assert_eq!(labeling.regions.len(), 3);
+
+        let b = labeling.region_at(Point { x: 2, y: 0 }).unwrap();
+        assert_eq!(b.area(), 4);
+        assert_eq!(b.perimeter, 10);
+        assert_eq!(b.bounding_box(), Rect::from_corners(Point { x: 1, y: 0 }, Point { x: 2, y: 2 }));
+
+        let only_a = label_regions(&g, Connectivity::Four, |&c| c == 'A');
+        assert_eq!(only_a.regions.len(), 1);
+        assert_eq!(only_a.label_at(Point { x: 2, y: 2 }), None);
+    }
+
+    #[test]
+    fn exterior() {
+        let g = grid("
+.....
+.###.
+.#.#.
+.###.
+.....
+");
+        let exterior = fill_exterior(&g, Connectivity::Four, |&c| c == '.');
+        assert!(exterior[0][0]);
+        assert!(exterior[4][2]);
+        assert!(!exterior[2][2]);
+        assert!(!exterior[1][1]);
+    }
+}
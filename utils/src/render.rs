@@ -0,0 +1,142 @@
+use std::{fmt, io};
+
+use crate::{color_mode, ColorMode, Style, StyledChar};
+
+pub(crate) const RESET: &str = "\x1b[0m";
+
+/// Write `input` with 24-bit ANSI colour escapes, one line per row.
+///
+/// Escapes are only emitted where the style changes, and every styled row ends with a reset.
+pub fn write_grid<W: fmt::Write>(out: &mut W, input: &[Vec<StyledChar>]) -> fmt::Result {
+    write_grid_with(out, input, ColorMode::TrueColor)
+}
+
+/// [`write_grid`] with colours downgraded to what `mode` supports
+pub fn write_grid_with<W: fmt::Write>(out: &mut W, input: &[Vec<StyledChar>], mode: ColorMode) -> fmt::Result {
+    if !mode.is_colored() {
+        return write_plain_grid(out, input);
+    }
+
+    for row in input {
+        let mut last_style = Style::default();
+
+        for tile in row {
+            if tile.style != last_style {
+                last_style = tile.style;
+                out.write_str(RESET)?;
+
+                if let Some(c) = tile.style.fg {
+                    out.write_str(&mode.fg(c))?;
+                }
+
+                if let Some(c) = tile.style.bg {
+                    out.write_str(&mode.bg(c))?;
+                }
+
+                out.write_str(&tile.style.attrs.escape())?;
+            }
+            out.write_char(tile.chr)?;
+        }
+
+        if last_style != Style::default() {
+            out.write_str(RESET)?;
+        }
+
+        out.write_char('\n')?;
+    }
+
+    Ok(())
+}
+
+/// Write only the characters of `input`, one line per row
+pub fn write_plain_grid<W: fmt::Write>(out: &mut W, input: &[Vec<StyledChar>]) -> fmt::Result {
+    for row in input {
+        row.iter().try_for_each(|tile| out.write_char(tile.chr))?;
+        out.write_char('\n')?;
+    }
+
+    Ok(())
+}
+
+/// [`write_grid_with`] for byte sinks like files and stdout
+pub fn write_grid_io<W: io::Write>(out: &mut W, input: &[Vec<StyledChar>], mode: ColorMode) -> io::Result<()> {
+    out.write_all(to_ansi_string_with(input, mode).as_bytes())
+}
+
+pub fn to_ansi_string(input: &[Vec<StyledChar>]) -> String {
+    to_ansi_string_with(input, ColorMode::TrueColor)
+}
+
+pub fn to_ansi_string_with(input: &[Vec<StyledChar>], mode: ColorMode) -> String {
+    let mut out = String::new();
+    write_grid_with(&mut out, input, mode).expect("writing to a String cannot fail");
+    out
+}
+
+pub fn to_plain_string(input: &[Vec<StyledChar>]) -> String {
+    let mut out = String::new();
+    write_plain_grid(&mut out, input).expect("writing to a String cannot fail");
+    out
+}
+
+/// Print to stdout in the current [`color_mode`]
+pub fn print_grid(input: &[Vec<StyledChar>]) {
+    write_grid_io(&mut io::stdout().lock(), input, color_mode()).expect("failed to write to stdout");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Attrs, Color, Overlay, Point, Precedence, StyleUtil};
+
+    fn sample() -> Vec<Vec<StyledChar>> {
+        let red = Style::fg(Some(Color::rgb(255, 0, 0)));
+        let mut grid: Vec<Vec<StyledChar>> = ["#..", ".#."].iter().map(|l| l.chars().map(StyledChar::of).collect()).collect();
+        grid[0][0].style = red;
+        grid[0][1].style = red;
+        grid[1][1].style = Style::bg(Some(Color::rgb(0, 0, 255)));
+        grid
+    }
+
+    #[test]
+    fn snapshots() {
+        let grid = sample();
+        assert_eq!(to_plain_string(&grid), "#..\n.#.\n");
+        assert_eq!(
+            to_ansi_string(&grid),
+            "\x1b[0m\x1b[38;2;255;0;0m#.\x1b[0m.\n.\x1b[0m\x1b[48;2;0;0;255m#\x1b[0m.\n",
+        );
+
+        let mut bytes = vec![];
+        write_grid_io(&mut bytes, &grid, ColorMode::TrueColor).unwrap();
+        assert_eq!(String::from_utf8(bytes).unwrap(), to_ansi_string(&grid));
+    }
+
+    #[test]
+    fn layered() {
+        let base: Vec<Vec<StyledChar>> = vec!["abc".chars().map(StyledChar::of).collect()];
+        let path = Overlay::uniform([Point { x: 0, y: 0 }, Point { x: 1, y: 0 }], Style::attrs(Attrs::UNDERLINE).with_attrs(Attrs::BOLD));
+        let current = Overlay::uniform([Point { x: 1, y: 0 }, Point { x: 9, y: 9 }], Style::fg(Some(Color::rgb(255, 0, 0))).with_attrs(Attrs::REVERSE));
+
+        let flat = base.flatten(&[path, current], Precedence::Overlay);
+        assert_eq!(
+            to_ansi_string(&flat),
+            "\x1b[0m\x1b[1m\x1b[4ma\x1b[0m\x1b[38;2;255;0;0m\x1b[1m\x1b[4m\x1b[7mb\x1b[0mc\n",
+        );
+        assert_eq!(to_plain_string(&flat), "abc\n");
+    }
+
+    #[test]
+    fn downgraded() {
+        let grid = sample();
+        assert_eq!(to_ansi_string_with(&grid, ColorMode::None), to_plain_string(&grid));
+        assert_eq!(
+            to_ansi_string_with(&grid, ColorMode::Ansi256),
+            "\x1b[0m\x1b[38;5;196m#.\x1b[0m.\n.\x1b[0m\x1b[48;5;21m#\x1b[0m.\n",
+        );
+        assert_eq!(
+            to_ansi_string_with(&grid, ColorMode::Ansi16),
+            "\x1b[0m\x1b[91m#.\x1b[0m.\n.\x1b[0m\x1b[44m#\x1b[0m.\n",
+        );
+    }
+}
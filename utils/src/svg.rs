@@ -0,0 +1,204 @@
+use std::{fmt::Write as _, fs, io, path::Path};
+
+use crate::{Color, Point};
+
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Fill and outline of a shape. Stroke widths are in output pixels, whatever the zoom.
+#[derive(Copy, Clone)]
+pub struct Paint {
+    pub fill: Option<Color>,
+    pub stroke: Option<Color>,
+    pub stroke_width: f64,
+    pub opacity: f64,
+}
+impl Default for Paint {
+    fn default() -> Self {
+        Self { fill: None, stroke: None, stroke_width: 1.0, opacity: 1.0 }
+    }
+}
+impl Paint {
+    pub fn fill(c: Color) -> Self {
+        Self { fill: Some(c), ..Default::default() }
+    }
+
+    pub fn stroke(c: Color, width: f64) -> Self {
+        Self { stroke: Some(c), stroke_width: width, ..Default::default() }
+    }
+
+    pub fn with_fill(mut self, c: Color) -> Self {
+        self.fill = Some(c);
+        self
+    }
+
+    pub fn with_stroke(mut self, c: Color, width: f64) -> Self {
+        self.stroke = Some(c);
+        self.stroke_width = width;
+        self
+    }
+
+    pub fn with_opacity(mut self, opacity: f64) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    fn attributes(&self) -> String {
+        let mut out = String::new();
+        write!(out, " fill=\"{}\"", self.fill.map_or("none".to_owned(), Color::to_hex)).unwrap();
+        if let Some(c) = self.stroke {
+            write!(out, " stroke=\"{}\" stroke-width=\"{}\" vector-effect=\"non-scaling-stroke\"", c.to_hex(), self.stroke_width).unwrap();
+        }
+        if self.opacity < 1.0 {
+            write!(out, " opacity=\"{}\"", self.opacity).unwrap();
+        }
+        out
+    }
+}
+
+/// An SVG drawing in puzzle coordinates (y grows downwards, like the grids).
+///
+/// The view box grows to fit everything drawn, so callers never have to work out a transform.
+#[derive(Clone)]
+pub struct Svg {
+    elements: Vec<String>,
+    min: Point<f64>,
+    max: Point<f64>,
+    /// size of the longer side of the output, in pixels
+    size: f64,
+}
+impl Default for Svg {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Svg {
+    pub fn new() -> Self {
+        Self {
+            elements: vec![],
+            min: Point { x: f64::INFINITY, y: f64::INFINITY },
+            max: Point { x: f64::NEG_INFINITY, y: f64::NEG_INFINITY },
+            size: 800.0,
+        }
+    }
+
+    pub fn with_size(mut self, size: f64) -> Self {
+        self.size = size;
+        self
+    }
+
+    fn include(&mut self, pt: Point<f64>) {
+        self.min = Point { x: self.min.x.min(pt.x), y: self.min.y.min(pt.y) };
+        self.max = Point { x: self.max.x.max(pt.x), y: self.max.y.max(pt.y) };
+    }
+
+    fn points_attr(&mut self, points: &[Point<f64>]) -> String {
+        points.iter().for_each(|&pt| self.include(pt));
+        points.iter().map(|pt| format!("{},{}", pt.x, pt.y)).collect::<Vec<_>>().join(" ")
+    }
+
+    /// Open path through all points
+    pub fn polyline(&mut self, points: &[Point<f64>], paint: Paint) -> &mut Self {
+        let points = self.points_attr(points);
+        self.elements.push(format!("<polyline points=\"{}\"{}/>", points, paint.attributes()));
+        self
+    }
+
+    /// Closed path through all points
+    pub fn polygon(&mut self, points: &[Point<f64>], paint: Paint) -> &mut Self {
+        let points = self.points_attr(points);
+        self.elements.push(format!("<polygon points=\"{}\"{}/>", points, paint.attributes()));
+        self
+    }
+
+    pub fn line(&mut self, a: Point<f64>, b: Point<f64>, paint: Paint) -> &mut Self {
+        self.include(a);
+        self.include(b);
+        self.elements.push(format!("<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"{}/>", a.x, a.y, b.x, b.y, paint.attributes()));
+        self
+    }
+
+    /// Rectangle between two opposite corners
+    pub fn rect(&mut self, a: Point<f64>, b: Point<f64>, paint: Paint) -> &mut Self {
+        self.include(a);
+        self.include(b);
+        let (x, y) = (a.x.min(b.x), a.y.min(b.y));
+        let (w, h) = ((a.x - b.x).abs(), (a.y - b.y).abs());
+        self.elements.push(format!("<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"{}/>", x, y, w, h, paint.attributes()));
+        self
+    }
+
+    pub fn circle(&mut self, center: Point<f64>, radius: f64, paint: Paint) -> &mut Self {
+        self.include(Point { x: center.x - radius, y: center.y - radius });
+        self.include(Point { x: center.x + radius, y: center.y + radius });
+        self.elements.push(format!("<circle cx=\"{}\" cy=\"{}\" r=\"{}\"{}/>", center.x, center.y, radius, paint.attributes()));
+        self
+    }
+
+    /// Text anchored at its baseline start; `size` is in puzzle units
+    pub fn text(&mut self, pos: Point<f64>, size: f64, color: Color, content: &str) -> &mut Self {
+        self.include(pos);
+        self.elements.push(format!(
+            "<text x=\"{}\" y=\"{}\" font-size=\"{}\" font-family=\"monospace\" fill=\"{}\">{}</text>",
+            pos.x, pos.y, size, color.to_hex(), escape(content),
+        ));
+        self
+    }
+
+    pub fn render(&self) -> String {
+        let (min, max) = if self.elements.is_empty() {
+            (Point { x: 0.0, y: 0.0 }, Point { x: 1.0, y: 1.0 })
+        } else {
+            (self.min, self.max)
+        };
+
+        // leave a small margin so strokes on the edge are not cut off
+        let margin = 0.02 * (max.x - min.x).max(max.y - min.y).max(1.0);
+        let (x, y) = (min.x - margin, min.y - margin);
+        let (w, h) = (max.x - min.x + 2.0 * margin, max.y - min.y + 2.0 * margin);
+        let scale = self.size / w.max(h);
+
+        let mut out = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"{} {} {} {}\">\n",
+            (w * scale).round(), (h * scale).round(), x, y, w, h,
+        );
+        for element in &self.elements {
+            out.push_str("  ");
+            out.push_str(element);
+            out.push('\n');
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.render())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pt(x: f64, y: f64) -> Point<f64> {
+        Point { x, y }
+    }
+
+    #[test]
+    fn drawing() {
+        let mut svg = Svg::new().with_size(100.0);
+        svg.polygon(&[pt(0.0, 0.0), pt(50.0, 0.0), pt(50.0, 25.0)], Paint::fill(Color::rgb(255, 0, 0)))
+            .rect(pt(10.0, 20.0), pt(0.0, 5.0), Paint::stroke(Color::BLACK, 2.0).with_opacity(0.5))
+            .text(pt(1.0, 2.0), 3.0, Color::WHITE, "a<b");
+
+        let out = svg.render();
+        assert!(out.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"100\" height=\"52\" viewBox=\"-1 -1 52 27\">\n"));
+        assert!(out.contains("  <polygon points=\"0,0 50,0 50,25\" fill=\"#ff0000\"/>\n"));
+        assert!(out.contains(
+            "  <rect x=\"0\" y=\"5\" width=\"10\" height=\"15\" fill=\"none\" stroke=\"#000000\" stroke-width=\"2\" vector-effect=\"non-scaling-stroke\" opacity=\"0.5\"/>\n"
+        ));
+        assert!(out.contains(">a&lt;b</text>"));
+        assert!(out.ends_with("</svg>\n"));
+    }
+}
@@ -0,0 +1,156 @@
+use std::{
+    env,
+    io::{self, IsTerminal},
+    sync::{Mutex, OnceLock},
+};
+
+use crate::Color;
+
+/// How much colour the output can show
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ColorMode {
+    /// 24-bit `38;2;r;g;b` escapes
+    TrueColor,
+    /// nearest entry of the xterm 256-colour palette
+    Ansi256,
+    /// nearest of the 16 basic colours
+    Ansi16,
+    /// no escapes at all
+    None,
+}
+impl ColorMode {
+    /// Decide from the relevant environment variables and whether stdout is a terminal
+    pub fn from_env(no_color: Option<&str>, term: Option<&str>, colorterm: Option<&str>, tty: bool) -> Self {
+        if no_color.is_some_and(|v| !v.is_empty()) || !tty || term == Some("dumb") {
+            ColorMode::None
+        } else if colorterm.is_some_and(|c| c == "truecolor" || c == "24bit") {
+            ColorMode::TrueColor
+        } else if term.is_some_and(|t| t.contains("256color")) {
+            ColorMode::Ansi256
+        } else {
+            ColorMode::Ansi16
+        }
+    }
+
+    pub fn detect() -> Self {
+        let var = |name| env::var(name).ok();
+        Self::from_env(var("NO_COLOR").as_deref(), var("TERM").as_deref(), var("COLORTERM").as_deref(), io::stdout().is_terminal())
+    }
+
+    /// Escape sequence selecting `c` as the foreground colour (empty without colour)
+    pub fn fg(self, c: Color) -> String {
+        self.escape(c, 38, 30)
+    }
+
+    /// Escape sequence selecting `c` as the background colour (empty without colour)
+    pub fn bg(self, c: Color) -> String {
+        self.escape(c, 48, 40)
+    }
+
+    /// `extended` is the 38/48 prefix, `basic` the code of black in the 16-colour set
+    fn escape(self, c: Color, extended: u8, basic: u8) -> String {
+        match self {
+            ColorMode::TrueColor => format!("\x1b[{};2;{};{};{}m", extended, c.r, c.g, c.b),
+            ColorMode::Ansi256 => format!("\x1b[{};5;{}m", extended, nearest_256(c)),
+            ColorMode::Ansi16 => {
+                let idx = nearest_16(c);
+                // the bright half lives 60 codes up
+                let code = if idx < 8 { basic + idx } else { basic + 60 + idx - 8 };
+                format!("\x1b[{}m", code)
+            }
+            ColorMode::None => String::new(),
+        }
+    }
+
+    pub fn is_colored(self) -> bool {
+        self != ColorMode::None
+    }
+}
+
+static FORCED: Mutex<Option<ColorMode>> = Mutex::new(None);
+static DETECTED: OnceLock<ColorMode> = OnceLock::new();
+
+/// The mode used for printing: the forced one if set, otherwise detected once per process
+pub fn color_mode() -> ColorMode {
+    let forced = *FORCED.lock().unwrap();
+    forced.unwrap_or_else(|| *DETECTED.get_or_init(ColorMode::detect))
+}
+
+/// Override detection; `None` goes back to the detected mode
+pub fn force_color_mode(mode: Option<ColorMode>) {
+    *FORCED.lock().unwrap() = mode;
+}
+
+fn dist_sq(a: Color, b: Color) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    d(a.r, b.r) + d(a.g, b.g) + d(a.b, b.b)
+}
+
+/// Channel levels of the 6x6x6 cube at 16..=231
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Closest xterm 256-colour index, from the colour cube or the grey ramp at 232..=255
+pub fn nearest_256(c: Color) -> u8 {
+    let level = |v: u8| (0..6).min_by_key(|&i| (CUBE_LEVELS[i] as i32 - v as i32).abs()).unwrap();
+    let (r, g, b) = (level(c.r), level(c.g), level(c.b));
+    let cube = Color::rgb(CUBE_LEVELS[r], CUBE_LEVELS[g], CUBE_LEVELS[b]);
+    let cube_idx = 16 + 36 * r + 6 * g + b;
+
+    let avg = (c.r as u32 + c.g as u32 + c.b as u32) / 3;
+    let grey = (avg.saturating_sub(3) / 10).min(23) as u8;
+    let grey_level = 8 + 10 * grey;
+    let grey_idx = 232 + grey;
+
+    if dist_sq(c, Color::rgb(grey_level, grey_level, grey_level)) < dist_sq(c, cube) {
+        grey_idx
+    } else {
+        cube_idx as u8
+    }
+}
+
+/// The xterm defaults for the 16 basic colours
+const BASIC: [Color; 16] = [
+    Color::rgb(0, 0, 0), Color::rgb(205, 0, 0), Color::rgb(0, 205, 0), Color::rgb(205, 205, 0),
+    Color::rgb(0, 0, 238), Color::rgb(205, 0, 205), Color::rgb(0, 205, 205), Color::rgb(229, 229, 229),
+    Color::rgb(127, 127, 127), Color::rgb(255, 0, 0), Color::rgb(0, 255, 0), Color::rgb(255, 255, 0),
+    Color::rgb(92, 92, 255), Color::rgb(255, 0, 255), Color::rgb(0, 255, 255), Color::rgb(255, 255, 255),
+];
+
+/// Closest of the 16 basic colours; 0..8 are normal and 8..16 bright
+pub fn nearest_16(c: Color) -> u8 {
+    (0..16).min_by_key(|&i| dist_sq(c, BASIC[i as usize])).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detection() {
+        let tty = |no_color, term, colorterm| ColorMode::from_env(no_color, term, colorterm, true);
+        assert_eq!(tty(None, Some("xterm-256color"), Some("truecolor")), ColorMode::TrueColor);
+        assert_eq!(tty(None, Some("xterm-256color"), None), ColorMode::Ansi256);
+        assert_eq!(tty(None, Some("xterm"), None), ColorMode::Ansi16);
+        assert_eq!(tty(None, Some("dumb"), Some("truecolor")), ColorMode::None);
+        assert_eq!(tty(Some("1"), Some("xterm-256color"), Some("truecolor")), ColorMode::None);
+        assert_eq!(tty(Some(""), Some("xterm"), None), ColorMode::Ansi16);
+        assert_eq!(ColorMode::from_env(None, Some("xterm-256color"), Some("truecolor"), false), ColorMode::None);
+    }
+
+    #[test]
+    fn downgrades() {
+        assert_eq!(nearest_256(Color::rgb(255, 0, 0)), 196);
+        assert_eq!(nearest_256(Color::rgb(0, 0, 0)), 16);
+        assert_eq!(nearest_256(Color::rgb(128, 128, 128)), 244);
+        assert_eq!(nearest_256(Color::rgb(100, 140, 170)), 16 + 36 + 2 * 6 + 3);
+        assert_eq!(nearest_16(Color::rgb(255, 10, 10)), 9);
+        assert_eq!(nearest_16(Color::rgb(190, 10, 10)), 1);
+
+        let red = Color::rgb(255, 0, 0);
+        assert_eq!(ColorMode::TrueColor.fg(red), "\x1b[38;2;255;0;0m");
+        assert_eq!(ColorMode::Ansi256.bg(red), "\x1b[48;5;196m");
+        assert_eq!(ColorMode::Ansi16.fg(red), "\x1b[91m");
+        assert_eq!(ColorMode::Ansi16.bg(Color::rgb(0, 0, 200)), "\x1b[44m");
+        assert_eq!(ColorMode::None.fg(red), "");
+    }
+}